@@ -0,0 +1,62 @@
+//! Checks that `#[derive(CramberryMessage)]` produces the exact golden
+//! wire layout (leading field-count varint, per-field tag + value, zigzag
+//! for signed ints) and registers itself with the global `Registry`.
+
+use cramberry::{Reader, Writer};
+use cramberry_derive::CramberryMessage;
+
+#[derive(CramberryMessage)]
+struct NestedMessage {
+    #[cramberry(field = 1)]
+    name: String,
+    #[cramberry(field = 2, wire = "svarint")]
+    value: i32,
+}
+
+#[test]
+fn matches_hand_written_golden_layout() {
+    let msg = NestedMessage {
+        name: "nested".to_string(),
+        value: 123,
+    };
+
+    let mut writer = Writer::new();
+    msg.encode(&mut writer).unwrap();
+    let got = writer.into_bytes();
+
+    // Hand-written equivalent of encode_nested_message in the interop
+    // test suite: field count, then tag 1 (Bytes) + string, tag 2
+    // (Varint, zigzag value) + svarint.
+    let mut expected = Writer::new();
+    expected.write_varint(2).unwrap();
+    expected.write_tag(1, cramberry::WireType::Bytes).unwrap();
+    expected.write_string("nested").unwrap();
+    expected.write_tag(2, cramberry::WireType::Varint).unwrap();
+    expected.write_svarint(123).unwrap();
+
+    assert_eq!(got, expected.into_bytes());
+}
+
+#[test]
+fn round_trips_through_encode_decode() {
+    let msg = NestedMessage {
+        name: "hello".to_string(),
+        value: -42,
+    };
+
+    let mut writer = Writer::new();
+    msg.encode(&mut writer).unwrap();
+    let bytes = writer.into_bytes();
+
+    let mut reader = Reader::new(&bytes);
+    let decoded = NestedMessage::decode(&mut reader).unwrap();
+
+    assert_eq!(decoded.name, "hello");
+    assert_eq!(decoded.value, -42);
+}
+
+#[test]
+fn auto_registers_with_the_global_registry() {
+    let registry = cramberry::global_registry();
+    assert!(registry.is_registered("NestedMessage"));
+}
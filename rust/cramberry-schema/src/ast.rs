@@ -0,0 +1,79 @@
+//! Abstract syntax tree for the `.cramberry` schema grammar.
+
+/// A parsed schema bundle: every `message` declared in one `.cramberry` file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schema {
+    pub messages: Vec<Message>,
+}
+
+/// A single `message Name { ... }` declaration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub name: String,
+    pub fields: Vec<Field>,
+}
+
+/// One `<number>: <wire_mode> <field_name>;` line inside a message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub number: u32,
+    pub wire_mode: WireMode,
+    pub name: String,
+}
+
+/// The wire-level encoding a field uses, matching one `Writer`/`Reader`
+/// method pair each. This is deliberately a flat enum of concrete
+/// encodings (not Rust types) because a schema author picks the wire
+/// behavior, not just a type: e.g. `varint` vs `svarint` both could back an
+/// integer field, but only one of them zigzag-encodes negative values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WireMode {
+    Bool,
+    /// Zigzag-encoded signed 32-bit integer.
+    SVarint,
+    /// Zigzag-encoded signed 64-bit integer.
+    SVarint64,
+    /// Unsigned 32-bit varint.
+    Varint,
+    /// Unsigned 64-bit varint.
+    Varint64,
+    /// 32-bit float, written as a fixed-width field.
+    Fixed32,
+    /// 64-bit float, written as a fixed-width field.
+    Fixed64,
+    String,
+    Bytes,
+}
+
+impl WireMode {
+    /// Parses a wire-mode keyword as it appears in a `.cramberry` file.
+    pub fn from_keyword(keyword: &str) -> Option<Self> {
+        Some(match keyword {
+            "bool" => WireMode::Bool,
+            "svarint" => WireMode::SVarint,
+            "svarint64" => WireMode::SVarint64,
+            "varint" => WireMode::Varint,
+            "varint64" => WireMode::Varint64,
+            "fixed32" => WireMode::Fixed32,
+            "fixed64" => WireMode::Fixed64,
+            "string" => WireMode::String,
+            "bytes" => WireMode::Bytes,
+            _ => return None,
+        })
+    }
+
+    /// The Rust type the generated struct field holds.
+    pub fn rust_type(&self) -> &'static str {
+        match self {
+            WireMode::Bool => "bool",
+            WireMode::SVarint => "i32",
+            WireMode::SVarint64 => "i64",
+            WireMode::Varint => "u32",
+            WireMode::Varint64 => "u64",
+            WireMode::Fixed32 => "f32",
+            WireMode::Fixed64 => "f64",
+            WireMode::String => "String",
+            WireMode::Bytes => "Vec<u8>",
+        }
+    }
+}
@@ -0,0 +1,279 @@
+//! Rust codegen backend: turns a parsed `Schema` into source text.
+//!
+//! The emitted `encode_*`/`decode_*` pair for each message matches the
+//! hand-written functions in the interop test suite and the layout
+//! `cramberry-derive` generates: a leading varint
+//! field count, then one `write_tag` + raw `write_*` call per field in
+//! declaration order. Keeping that layout is the whole point of this
+//! crate — it lets a single `.cramberry` file drive golden-file generation
+//! for both this runtime and the Go one without the two drifting apart.
+
+use std::fmt::Write as _;
+
+use crate::ast::{Message, Schema, WireMode};
+
+/// Per-`WireMode` pieces the codegen needs: the `WireType` the field's tag
+/// is written with, the `Writer`/`Reader` method names, and whether
+/// `decode` must convert the reader's return value (`&str`/`&[u8]`) into
+/// an owned one.
+struct WireOps {
+    wire_type: &'static str,
+    write_method: &'static str,
+    read_method: &'static str,
+    owns_on_decode: bool,
+}
+
+fn wire_ops(mode: WireMode) -> WireOps {
+    match mode {
+        WireMode::Bool => WireOps {
+            wire_type: "Varint",
+            write_method: "write_bool",
+            read_method: "read_bool",
+            owns_on_decode: false,
+        },
+        // Tagged `Varint`, not `SVarint`: the golden layout zigzag-encodes
+        // the *value* but still declares the tag's wire type as the plain
+        // varint one, matching the hand-written interop encoders.
+        WireMode::SVarint => WireOps {
+            wire_type: "Varint",
+            write_method: "write_svarint",
+            read_method: "read_svarint",
+            owns_on_decode: false,
+        },
+        WireMode::SVarint64 => WireOps {
+            wire_type: "Varint",
+            write_method: "write_svarint64",
+            read_method: "read_svarint64",
+            owns_on_decode: false,
+        },
+        WireMode::Varint => WireOps {
+            wire_type: "Varint",
+            write_method: "write_varint",
+            read_method: "read_varint",
+            owns_on_decode: false,
+        },
+        WireMode::Varint64 => WireOps {
+            wire_type: "Varint",
+            write_method: "write_varint64",
+            read_method: "read_varint64",
+            owns_on_decode: false,
+        },
+        WireMode::Fixed32 => WireOps {
+            wire_type: "Fixed32",
+            write_method: "write_float32",
+            read_method: "read_float32",
+            owns_on_decode: false,
+        },
+        WireMode::Fixed64 => WireOps {
+            wire_type: "Fixed64",
+            write_method: "write_float64",
+            read_method: "read_float64",
+            owns_on_decode: false,
+        },
+        WireMode::String => WireOps {
+            wire_type: "Bytes",
+            write_method: "write_string",
+            read_method: "read_string",
+            owns_on_decode: true,
+        },
+        WireMode::Bytes => WireOps {
+            wire_type: "Bytes",
+            write_method: "write_length_prefixed_bytes",
+            read_method: "read_length_prefixed_bytes",
+            owns_on_decode: true,
+        },
+    }
+}
+
+/// `value`/`&value` at an encode call site: scalar (`Copy`) wire modes pass
+/// by value, `String`/`Vec<u8>` by reference, matching `Writer`'s own
+/// `write_*`/`write_*_field` signatures.
+fn encode_arg(mode: WireMode, expr: &str) -> String {
+    match mode {
+        WireMode::String | WireMode::Bytes => format!("&{}", expr),
+        _ => expr.to_string(),
+    }
+}
+
+fn default_value(mode: WireMode) -> &'static str {
+    match mode {
+        WireMode::Bool => "false",
+        WireMode::SVarint | WireMode::Varint => "0",
+        WireMode::SVarint64 | WireMode::Varint64 => "0",
+        WireMode::Fixed32 => "0.0",
+        WireMode::Fixed64 => "0.0",
+        WireMode::String => "::std::string::String::new()",
+        WireMode::Bytes => "::std::vec::Vec::new()",
+    }
+}
+
+/// Converts a `PascalCase` (or already-`snake_case`) message name into
+/// `snake_case` for use in generated function names.
+fn to_snake_case(name: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn emit_struct(out: &mut String, message: &Message) {
+    let _ = writeln!(out, "#[derive(Debug, Clone, PartialEq)]");
+    let _ = writeln!(out, "pub struct {} {{", message.name);
+    for field in &message.fields {
+        let _ = writeln!(out, "    pub {}: {},", field.name, field.wire_mode.rust_type());
+    }
+    let _ = writeln!(out, "}}");
+}
+
+fn emit_encode(out: &mut String, message: &Message, snake_name: &str) {
+    let _ = writeln!(
+        out,
+        "pub fn encode_{}(writer: &mut ::cramberry::Writer, msg: &{}) -> ::cramberry::Result<()> {{",
+        snake_name, message.name
+    );
+    let _ = writeln!(out, "    writer.write_varint({})?;", message.fields.len());
+    for field in &message.fields {
+        let ops = wire_ops(field.wire_mode);
+        let _ = writeln!(
+            out,
+            "    writer.write_tag({}, ::cramberry::WireType::{})?;",
+            field.number, ops.wire_type
+        );
+        let arg = encode_arg(field.wire_mode, &format!("msg.{}", field.name));
+        let _ = writeln!(out, "    writer.{}({})?;", ops.write_method, arg);
+    }
+    let _ = writeln!(out, "    Ok(())");
+    let _ = writeln!(out, "}}");
+}
+
+fn emit_decode(out: &mut String, message: &Message, snake_name: &str) {
+    let _ = writeln!(
+        out,
+        "pub fn decode_{}(reader: &mut ::cramberry::Reader) -> ::cramberry::Result<{}> {{",
+        snake_name, message.name
+    );
+    let _ = writeln!(out, "    let field_count = reader.read_varint()?;");
+    for field in &message.fields {
+        let _ = writeln!(
+            out,
+            "    let mut {} = {};",
+            field.name,
+            default_value(field.wire_mode)
+        );
+    }
+    let _ = writeln!(out, "    for _ in 0..field_count {{");
+    let _ = writeln!(out, "        let tag = reader.read_tag()?;");
+    let _ = writeln!(out, "        match tag.field_number {{");
+    for field in &message.fields {
+        let ops = wire_ops(field.wire_mode);
+        let conv = if ops.owns_on_decode { ".to_owned()" } else { "" };
+        let _ = writeln!(
+            out,
+            "            {} => {{ {} = reader.{}()?{}; }}",
+            field.number, field.name, ops.read_method, conv
+        );
+    }
+    let _ = writeln!(out, "            _ => reader.skip_field(tag)?,");
+    let _ = writeln!(out, "        }}");
+    let _ = writeln!(out, "    }}");
+    let _ = write!(out, "    Ok({} {{", message.name);
+    for field in &message.fields {
+        let _ = write!(out, " {},", field.name);
+    }
+    let _ = writeln!(out, " }})");
+    let _ = writeln!(out, "}}");
+}
+
+fn emit_register(out: &mut String, message: &Message, snake_name: &str) {
+    let _ = writeln!(
+        out,
+        "pub fn register_{}(registry: &::cramberry::Registry) -> ::cramberry::TypeId {{",
+        snake_name
+    );
+    let _ = writeln!(
+        out,
+        "    registry.register::<{}>(\"{}\", encode_{}, decode_{})",
+        message.name, message.name, snake_name, snake_name
+    );
+    let _ = writeln!(out, "}}");
+}
+
+/// Generates Rust source for every message in `schema`: a struct, its
+/// `encode_*`/`decode_*` free functions (matching `Registry`'s
+/// `Encoder<T>`/`Decoder<T>` function-pointer signatures), and a
+/// `register_*` helper that registers the pair under the message's name.
+///
+/// The returned string is a complete, if unformatted, Rust module body —
+/// write it to a `.rs` file and `include!`/`mod` it like any other
+/// generated code.
+pub fn generate(schema: &Schema) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "// @generated by cramberry-schema. Do not edit by hand.");
+    let _ = writeln!(out);
+
+    for (i, message) in schema.messages.iter().enumerate() {
+        if i > 0 {
+            let _ = writeln!(out);
+        }
+        let snake_name = to_snake_case(&message.name);
+        emit_struct(&mut out, message);
+        let _ = writeln!(out);
+        emit_encode(&mut out, message, &snake_name);
+        let _ = writeln!(out);
+        emit_decode(&mut out, message, &snake_name);
+        let _ = writeln!(out);
+        emit_register(&mut out, message, &snake_name);
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_generate_matches_hand_written_shape() {
+        let schema = parse(
+            r#"
+            message NestedMessage {
+                1: string name;
+                2: svarint value;
+            }
+            "#,
+        )
+        .unwrap();
+
+        let generated = generate(&schema);
+        assert!(generated.contains("pub struct NestedMessage {"));
+        assert!(generated.contains("pub name: String,"));
+        assert!(generated.contains("pub value: i32,"));
+        assert!(generated.contains("writer.write_varint(2)?;"));
+        assert!(generated.contains("writer.write_tag(1, ::cramberry::WireType::Bytes)?;"));
+        assert!(generated.contains("writer.write_string(&msg.name)?;"));
+        assert!(generated.contains("writer.write_tag(2, ::cramberry::WireType::Varint)?;"));
+        assert!(generated.contains("writer.write_svarint(msg.value)?;"));
+        assert!(generated.contains("let field_count = reader.read_varint()?;"));
+        assert!(generated.contains("name = reader.read_string()?.to_owned();"));
+        assert!(generated.contains("value = reader.read_svarint()?;"));
+        assert!(generated.contains(
+            "registry.register::<NestedMessage>(\"NestedMessage\", encode_nested_message, decode_nested_message)"
+        ));
+    }
+
+    #[test]
+    fn test_to_snake_case() {
+        assert_eq!(to_snake_case("NestedMessage"), "nested_message");
+        assert_eq!(to_snake_case("AllFieldNumbers"), "all_field_numbers");
+        assert_eq!(to_snake_case("already_snake"), "already_snake");
+    }
+}
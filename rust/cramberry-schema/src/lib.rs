@@ -0,0 +1,58 @@
+//! Compiler for the `.cramberry` schema grammar.
+//!
+//! A `.cramberry` file declares message shapes once:
+//!
+//! ```text
+//! message NestedMessage {
+//!     1: string name;
+//!     2: svarint value;
+//! }
+//! ```
+//!
+//! `compile` turns that into a Rust source string containing the struct,
+//! its `encode_*`/`decode_*` functions, and a `register_*` helper — the
+//! same shape of code the interop test suite currently hand-writes and
+//! keeps in lock-step with the Go runtime by hand. Driving both runtimes'
+//! golden-file generators off the same schema file removes that manual
+//! step.
+
+mod ast;
+mod codegen;
+mod parser;
+
+pub use ast::{Field, Message, Schema, WireMode};
+pub use parser::{parse, SchemaError};
+
+/// Parses `source` and generates its Rust module body in one call.
+pub fn compile(source: &str) -> Result<String, SchemaError> {
+    let schema = parser::parse(source)?;
+    Ok(codegen::generate(&schema))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compile_end_to_end() {
+        let generated = compile(
+            r#"
+            message Point {
+                1: svarint x;
+                2: svarint y;
+            }
+            "#,
+        )
+        .unwrap();
+
+        assert!(generated.contains("pub struct Point {"));
+        assert!(generated.contains("pub fn encode_point"));
+        assert!(generated.contains("pub fn decode_point"));
+        assert!(generated.contains("pub fn register_point"));
+    }
+
+    #[test]
+    fn test_compile_propagates_parse_errors() {
+        assert!(compile("message {").is_err());
+    }
+}
@@ -0,0 +1,30 @@
+//! `cramberryc` — compiles a `.cramberry` schema file to a Rust source file.
+//!
+//! ```text
+//! cramberryc <schema.cramberry> <out.rs>
+//! ```
+
+use std::{env, fs, process};
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 3 {
+        eprintln!("usage: cramberryc <schema.cramberry> <out.rs>");
+        process::exit(2);
+    }
+
+    let source = fs::read_to_string(&args[1]).unwrap_or_else(|err| {
+        eprintln!("failed to read {}: {}", args[1], err);
+        process::exit(1);
+    });
+
+    let generated = cramberry_schema::compile(&source).unwrap_or_else(|err| {
+        eprintln!("{}: {}", args[1], err);
+        process::exit(1);
+    });
+
+    fs::write(&args[2], generated).unwrap_or_else(|err| {
+        eprintln!("failed to write {}: {}", args[2], err);
+        process::exit(1);
+    });
+}
@@ -0,0 +1,329 @@
+//! Parser for the `.cramberry` schema grammar.
+//!
+//! Grammar (informal):
+//!
+//! ```text
+//! schema   := message*
+//! message  := "message" ident "{" field* "}"
+//! field    := number ":" wire_mode ident ";"
+//! wire_mode := "bool" | "svarint" | "svarint64" | "varint" | "varint64"
+//!            | "fixed32" | "fixed64" | "string" | "bytes"
+//! ```
+//!
+//! `//` starts a line comment. Field numbers must be unique within a
+//! message; message names must be unique within a schema.
+
+use std::fmt;
+
+use crate::ast::{Field, Message, Schema, WireMode};
+
+/// A schema file failed to parse or validate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaError {
+    pub message: String,
+}
+
+impl SchemaError {
+    fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    Number(u32),
+    LBrace,
+    RBrace,
+    Colon,
+    Semicolon,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, SchemaError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '/' => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    for c in chars.by_ref() {
+                        if c == '\n' {
+                            break;
+                        }
+                    }
+                } else {
+                    return Err(SchemaError::new("expected '//' to start a comment"));
+                }
+            }
+            '{' => {
+                chars.next();
+                tokens.push(Token::LBrace);
+            }
+            '}' => {
+                chars.next();
+                tokens.push(Token::RBrace);
+            }
+            ':' => {
+                chars.next();
+                tokens.push(Token::Colon);
+            }
+            ';' => {
+                chars.next();
+                tokens.push(Token::Semicolon);
+            }
+            c if c.is_ascii_digit() => {
+                let mut number = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_ascii_digit() {
+                        number.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                let value = number
+                    .parse::<u32>()
+                    .map_err(|_| SchemaError::new(format!("invalid field number '{}'", number)))?;
+                tokens.push(Token::Number(value));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut ident = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_alphanumeric() || c == '_' {
+                        ident.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(Token::Ident(ident));
+            }
+            other => return Err(SchemaError::new(format!("unexpected character '{}'", other))),
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: &Token) -> Result<(), SchemaError> {
+        match self.next() {
+            Some(ref token) if token == expected => Ok(()),
+            other => Err(SchemaError::new(format!(
+                "expected {:?}, found {:?}",
+                expected, other
+            ))),
+        }
+    }
+
+    fn expect_ident(&mut self) -> Result<String, SchemaError> {
+        match self.next() {
+            Some(Token::Ident(name)) => Ok(name),
+            other => Err(SchemaError::new(format!(
+                "expected identifier, found {:?}",
+                other
+            ))),
+        }
+    }
+
+    fn expect_number(&mut self) -> Result<u32, SchemaError> {
+        match self.next() {
+            Some(Token::Number(n)) => Ok(n),
+            other => Err(SchemaError::new(format!(
+                "expected field number, found {:?}",
+                other
+            ))),
+        }
+    }
+
+    fn parse_schema(&mut self) -> Result<Schema, SchemaError> {
+        let mut messages = Vec::new();
+        while self.peek().is_some() {
+            messages.push(self.parse_message()?);
+        }
+        Ok(Schema { messages })
+    }
+
+    fn parse_message(&mut self) -> Result<Message, SchemaError> {
+        match self.next() {
+            Some(Token::Ident(keyword)) if keyword == "message" => {}
+            other => {
+                return Err(SchemaError::new(format!(
+                    "expected 'message', found {:?}",
+                    other
+                )))
+            }
+        }
+
+        let name = self.expect_ident()?;
+        self.expect(&Token::LBrace)?;
+
+        let mut fields = Vec::new();
+        while !matches!(self.peek(), Some(Token::RBrace)) {
+            fields.push(self.parse_field()?);
+        }
+        self.expect(&Token::RBrace)?;
+
+        Ok(Message { name, fields })
+    }
+
+    fn parse_field(&mut self) -> Result<Field, SchemaError> {
+        let number = self.expect_number()?;
+        self.expect(&Token::Colon)?;
+        let wire_mode_keyword = self.expect_ident()?;
+        let wire_mode = WireMode::from_keyword(&wire_mode_keyword).ok_or_else(|| {
+            SchemaError::new(format!("unknown wire mode '{}'", wire_mode_keyword))
+        })?;
+        let name = self.expect_ident()?;
+        self.expect(&Token::Semicolon)?;
+
+        Ok(Field {
+            number,
+            wire_mode,
+            name,
+        })
+    }
+}
+
+/// Parses a `.cramberry` schema source file into a `Schema` AST.
+///
+/// Validates that field numbers are unique within each message and that
+/// message names are unique within the schema; the codegen backend relies
+/// on both invariants.
+pub fn parse(source: &str) -> Result<Schema, SchemaError> {
+    let tokens = tokenize(source)?;
+    let schema = Parser { tokens, pos: 0 }.parse_schema()?;
+    validate(&schema)?;
+    Ok(schema)
+}
+
+fn validate(schema: &Schema) -> Result<(), SchemaError> {
+    let mut seen_messages = std::collections::HashSet::new();
+    for message in &schema.messages {
+        if !seen_messages.insert(&message.name) {
+            return Err(SchemaError::new(format!(
+                "duplicate message name '{}'",
+                message.name
+            )));
+        }
+
+        let mut seen_fields = std::collections::HashSet::new();
+        for field in &message.fields {
+            if !seen_fields.insert(field.number) {
+                return Err(SchemaError::new(format!(
+                    "duplicate field number {} in message '{}'",
+                    field.number, message.name
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_single_message() {
+        let schema = parse(
+            r#"
+            message NestedMessage {
+                1: string name;
+                2: svarint value;
+            }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(schema.messages.len(), 1);
+        let message = &schema.messages[0];
+        assert_eq!(message.name, "NestedMessage");
+        assert_eq!(message.fields.len(), 2);
+        assert_eq!(message.fields[0].wire_mode, WireMode::String);
+        assert_eq!(message.fields[1].wire_mode, WireMode::SVarint);
+    }
+
+    #[test]
+    fn test_parse_ignores_comments() {
+        let schema = parse(
+            r#"
+            // a comment
+            message Foo {
+                1: bool flag; // trailing comment
+            }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(schema.messages[0].fields[0].name, "flag");
+    }
+
+    #[test]
+    fn test_parse_multiple_messages() {
+        let schema = parse(
+            r#"
+            message A { 1: varint x; }
+            message B { 1: bytes y; }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(schema.messages.len(), 2);
+        assert_eq!(schema.messages[1].name, "B");
+    }
+
+    #[test]
+    fn test_duplicate_field_number_rejected() {
+        let err = parse(
+            r#"
+            message Foo {
+                1: bool a;
+                1: bool b;
+            }
+            "#,
+        )
+        .unwrap_err();
+        assert!(err.message.contains("duplicate field number"));
+    }
+
+    #[test]
+    fn test_duplicate_message_name_rejected() {
+        let err = parse("message Foo { 1: bool a; } message Foo { 1: bool b; }").unwrap_err();
+        assert!(err.message.contains("duplicate message name"));
+    }
+
+    #[test]
+    fn test_unknown_wire_mode_rejected() {
+        let err = parse("message Foo { 1: widget a; }").unwrap_err();
+        assert!(err.message.contains("unknown wire mode"));
+    }
+}
@@ -0,0 +1,168 @@
+//! `Encode`/`Decode` trait pair for user-extensible wire types.
+//!
+//! `Registry` already supports polymorphic dispatch, but only through a
+//! bespoke `Encoder<T>`/`Decoder<T>` function pair per type. `Encode`/
+//! `Decode` let a type describe its own wire format once and compose: a
+//! blanket impl over `Vec<T>` or `Option<T>` works for any `T` that
+//! implements the trait, without a hand-written fn for every combination.
+//! `Registry::register_type` bridges a `T: Encode + Decode` into the
+//! existing fn-pointer registry, so both styles stay usable side by side.
+
+use crate::error::Result;
+use crate::reader::Reader;
+use crate::writer::Writer;
+
+/// A type that can write itself to a `Writer`.
+///
+/// Unlike `write_*_field`, `encode` writes only the value — no field tag —
+/// mirroring `Writer`'s untagged `write_bool`/`write_string`/... methods.
+/// Callers that need a tagged field wrap the call themselves, the same way
+/// `write_map_field` wraps its body in a `Bytes` field.
+pub trait Encode {
+    fn encode(&self, writer: &mut Writer) -> Result<()>;
+}
+
+/// A type that can read itself back from a `Reader`, the `Decode` half of
+/// `Encode`.
+pub trait Decode: Sized {
+    fn decode(reader: &mut Reader) -> Result<Self>;
+}
+
+macro_rules! impl_scalar_codec {
+    ($ty:ty, $write:ident, $read:ident) => {
+        impl Encode for $ty {
+            fn encode(&self, writer: &mut Writer) -> Result<()> {
+                writer.$write(*self)
+            }
+        }
+
+        impl Decode for $ty {
+            fn decode(reader: &mut Reader) -> Result<Self> {
+                reader.$read()
+            }
+        }
+    };
+}
+
+impl_scalar_codec!(bool, write_bool, read_bool);
+impl_scalar_codec!(i32, write_svarint, read_svarint);
+impl_scalar_codec!(i64, write_svarint64, read_svarint64);
+impl_scalar_codec!(u32, write_varint, read_varint);
+impl_scalar_codec!(u64, write_varint64, read_varint64);
+impl_scalar_codec!(f32, write_float32, read_float32);
+impl_scalar_codec!(f64, write_float64, read_float64);
+
+impl Encode for String {
+    fn encode(&self, writer: &mut Writer) -> Result<()> {
+        writer.write_string(self)
+    }
+}
+
+impl Decode for String {
+    fn decode(reader: &mut Reader) -> Result<Self> {
+        Ok(reader.read_string()?.to_owned())
+    }
+}
+
+/// Encoded as length-prefixed raw bytes, same as `write_length_prefixed_bytes`.
+/// `u8` itself has no `Encode`/`Decode` impl, so this doesn't overlap with
+/// the blanket `Vec<T: Encode>` impl below.
+impl Encode for Vec<u8> {
+    fn encode(&self, writer: &mut Writer) -> Result<()> {
+        writer.write_length_prefixed_bytes(self)
+    }
+}
+
+impl Decode for Vec<u8> {
+    fn decode(reader: &mut Reader) -> Result<Self> {
+        Ok(reader.read_length_prefixed_bytes()?.to_vec())
+    }
+}
+
+/// A varint count followed by each element encoded into its own
+/// length-prefixed slot, so decoding one element can't run past another's
+/// bytes even if `T`'s encoding is variable-length.
+impl<T: Encode> Encode for Vec<T> {
+    fn encode(&self, writer: &mut Writer) -> Result<()> {
+        writer.write_varint(self.len() as u32)?;
+        for item in self {
+            let mut scratch = Writer::new();
+            item.encode(&mut scratch)?;
+            writer.write_length_prefixed_bytes(scratch.as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Decode> Decode for Vec<T> {
+    fn decode(reader: &mut Reader) -> Result<Self> {
+        let count = reader.read_varint()? as usize;
+        let mut values = Vec::with_capacity(count);
+        for _ in 0..count {
+            let bytes = reader.read_length_prefixed_bytes()?;
+            values.push(T::decode(&mut Reader::new(bytes))?);
+        }
+        Ok(values)
+    }
+}
+
+/// A presence flag (`write_bool`) followed by the value if present.
+impl<T: Encode> Encode for Option<T> {
+    fn encode(&self, writer: &mut Writer) -> Result<()> {
+        match self {
+            Some(value) => {
+                writer.write_bool(true)?;
+                value.encode(writer)
+            }
+            None => writer.write_bool(false),
+        }
+    }
+}
+
+impl<T: Decode> Decode for Option<T> {
+    fn decode(reader: &mut Reader) -> Result<Self> {
+        if reader.read_bool()? {
+            Ok(Some(T::decode(reader)?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip<T: Encode + Decode + PartialEq + std::fmt::Debug>(value: T) {
+        let mut writer = Writer::new();
+        value.encode(&mut writer).unwrap();
+        let data = writer.into_bytes();
+        let mut reader = Reader::new(&data);
+        assert_eq!(T::decode(&mut reader).unwrap(), value);
+    }
+
+    #[test]
+    fn test_scalar_round_trip() {
+        round_trip(true);
+        round_trip(-42i32);
+        round_trip(-9_000_000_000i64);
+        round_trip(42u32);
+        round_trip(9_000_000_000u64);
+        round_trip(3.25f32);
+        round_trip(2.5f64);
+        round_trip("hello".to_string());
+        round_trip(vec![0xde, 0xad, 0xbe, 0xefu8]);
+    }
+
+    #[test]
+    fn test_vec_of_encode_round_trip() {
+        round_trip(vec![1i32, -2, 3, -4]);
+        round_trip(vec!["a".to_string(), "bb".to_string(), "ccc".to_string()]);
+    }
+
+    #[test]
+    fn test_option_round_trip() {
+        round_trip(Some(7i32));
+        round_trip(None::<i32>);
+    }
+}
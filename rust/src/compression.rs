@@ -0,0 +1,82 @@
+//! Optional per-field DEFLATE compression for length-delimited payloads.
+//!
+//! The wire format itself has no notion of a compressed field: a
+//! `write_compressed_bytes_field`/`read_compressed_bytes` pair still emits
+//! an ordinary `Bytes` field, but prefixes the payload with one sidecar
+//! flag byte so a reader that knows a field is compressed can tell it
+//! apart from a plain `Bytes` payload written by `write_bytes_field`.
+
+use std::io::{Read, Write};
+
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+
+use crate::error::{Error, Result};
+
+/// Flag byte marking a payload as DEFLATE-compressed.
+pub(crate) const COMPRESSED_FLAG: u8 = 0x01;
+
+/// Deflate-compresses `data`, returning `[COMPRESSED_FLAG] + deflate(data)`.
+pub(crate) fn compress(data: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).map_err(Error::from)?;
+    let compressed = encoder.finish().map_err(Error::from)?;
+
+    let mut payload = Vec::with_capacity(compressed.len() + 1);
+    payload.push(COMPRESSED_FLAG);
+    payload.extend_from_slice(&compressed);
+    Ok(payload)
+}
+
+/// Inverse of `compress`: strips the flag byte and inflates the rest,
+/// refusing to produce more than `max_inflated_size` bytes so a crafted
+/// payload can't be used as a decompression bomb.
+pub(crate) fn decompress(payload: &[u8], max_inflated_size: usize) -> Result<Vec<u8>> {
+    let (&flag, rest) = payload
+        .split_first()
+        .ok_or_else(|| Error::custom("compressed field payload is empty"))?;
+    if flag != COMPRESSED_FLAG {
+        return Err(Error::custom(format!(
+            "unrecognized compressed field flag byte: {}",
+            flag
+        )));
+    }
+
+    let mut decoder = DeflateDecoder::new(rest);
+    let mut out = Vec::new();
+    let mut limited = (&mut decoder).take(max_inflated_size as u64 + 1);
+    limited.read_to_end(&mut out).map_err(Error::from)?;
+
+    if out.len() > max_inflated_size {
+        return Err(Error::LengthLimitExceeded {
+            requested: out.len(),
+            limit: max_inflated_size,
+        });
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compress_decompress_round_trip() {
+        let data = b"the quick brown fox jumps over the lazy dog".repeat(8);
+        let compressed = compress(&data).unwrap();
+        let decompressed = decompress(&compressed, data.len()).unwrap();
+        assert_eq!(decompressed, data);
+    }
+
+    #[test]
+    fn test_decompress_rejects_oversized_output() {
+        let data = vec![0u8; 1024];
+        let compressed = compress(&data).unwrap();
+        assert!(matches!(
+            decompress(&compressed, 16).unwrap_err(),
+            Error::LengthLimitExceeded { .. }
+        ));
+    }
+}
@@ -0,0 +1,305 @@
+//! `serde::Deserializer` backend over `Reader`.
+//!
+//! Mirrors `crate::ser`: sequences, maps, and structs all begin with a
+//! leading element/field-count varint, and struct fields are looked up by
+//! the same 1-based positional field number `ser::Serializer` assigns.
+
+use serde::de::{
+    self, DeserializeSeed, EnumAccess, MapAccess, SeqAccess, VariantAccess, Visitor,
+};
+use serde::Deserialize;
+
+use crate::error::{Error, Result};
+use crate::reader::Reader;
+
+impl de::Error for Error {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Error::custom(msg.to_string())
+    }
+}
+
+/// Deserializes a `T` previously written by `crate::ser::to_bytes`.
+pub fn from_bytes<'a, T: Deserialize<'a>>(data: &'a [u8]) -> Result<T> {
+    let mut reader = Reader::new(data);
+    from_reader(&mut reader)
+}
+
+/// Deserializes a `T` starting at the reader's current position.
+pub fn from_reader<'a, T: Deserialize<'a>>(reader: &mut Reader<'a>) -> Result<T> {
+    T::deserialize(&mut Deserializer { reader })
+}
+
+/// Deserializer that reads values from a `Reader`.
+pub struct Deserializer<'a, 'de> {
+    reader: &'a mut Reader<'de>,
+}
+
+impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'_, 'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value> {
+        Err(Error::custom(
+            "cramberry's serde backend requires a concrete type; deserialize_any is not supported",
+        ))
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_bool(self.reader.read_bool()?)
+    }
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_i8(self.reader.read_svarint()? as i8)
+    }
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_i16(self.reader.read_svarint()? as i16)
+    }
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_i32(self.reader.read_svarint()?)
+    }
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_i64(self.reader.read_svarint64()?)
+    }
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_u8(self.reader.read_varint()? as u8)
+    }
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_u16(self.reader.read_varint()? as u16)
+    }
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_u32(self.reader.read_varint()?)
+    }
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_u64(self.reader.read_varint64()?)
+    }
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_f32(self.reader.read_float32()?)
+    }
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_f64(self.reader.read_float64()?)
+    }
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let s = self.reader.read_string()?;
+        let c = s
+            .chars()
+            .next()
+            .ok_or_else(|| Error::custom("expected a single-character string"))?;
+        visitor.visit_char(c)
+    }
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_borrowed_str(self.reader.read_string()?)
+    }
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_str(visitor)
+    }
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_borrowed_bytes(self.reader.read_length_prefixed_bytes()?)
+    }
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_bytes(visitor)
+    }
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        if self.reader.read_bool()? {
+            visitor.visit_some(self)
+        } else {
+            visitor.visit_none()
+        }
+    }
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let len = self.reader.read_varint()? as usize;
+        visitor.visit_seq(CountedAccess { deserializer: self, remaining: len })
+    }
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let len = self.reader.read_varint()? as usize;
+        visitor.visit_map(CountedAccess { deserializer: self, remaining: len })
+    }
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        let len = self.reader.read_varint()? as usize;
+        visitor.visit_map(StructFieldAccess { deserializer: self, fields, remaining: len })
+    }
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_enum(EnumDeserializer { deserializer: self })
+    }
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_u64(visitor)
+    }
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let tag = self.reader.read_tag()?;
+        self.reader.skip_field(tag)?;
+        visitor.visit_unit()
+    }
+}
+
+/// `SeqAccess`/`MapAccess` for a count-prefixed run of elements (used for
+/// `Vec<T>`, tuples, and `HashMap<K, V>`).
+struct CountedAccess<'a, 'b, 'de> {
+    deserializer: &'a mut Deserializer<'b, 'de>,
+    remaining: usize,
+}
+
+impl<'a, 'b, 'de> SeqAccess<'de> for CountedAccess<'a, 'b, 'de> {
+    type Error = Error;
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.deserializer).map(Some)
+    }
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+impl<'a, 'b, 'de> MapAccess<'de> for CountedAccess<'a, 'b, 'de> {
+    type Error = Error;
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        seed.deserialize(&mut *self.deserializer).map(Some)
+    }
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.deserializer)
+    }
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+/// `MapAccess` used for `deserialize_struct`: reads `remaining` tagged
+/// fields, looking up the declared field name by positional field number
+/// (the same 1-based numbering `ser::StructSerializer` assigns on encode).
+struct StructFieldAccess<'a, 'b, 'de> {
+    deserializer: &'a mut Deserializer<'b, 'de>,
+    fields: &'static [&'static str],
+    remaining: usize,
+}
+
+impl<'a, 'b, 'de> MapAccess<'de> for StructFieldAccess<'a, 'b, 'de> {
+    type Error = Error;
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        let tag = self.deserializer.reader.read_tag()?;
+        let index = (tag.field_number as usize).saturating_sub(1);
+        let name = self.fields.get(index).copied().unwrap_or("__unknown");
+        seed.deserialize(de::value::BorrowedStrDeserializer::new(name)).map(Some)
+    }
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        seed.deserialize(&mut *self.deserializer)
+    }
+}
+
+/// `EnumAccess`/`VariantAccess` for C-like and data-carrying enums: the
+/// variant index is written as a leading varint, as in
+/// `ser::Serializer::serialize_*_variant`.
+struct EnumDeserializer<'a, 'b, 'de> {
+    deserializer: &'a mut Deserializer<'b, 'de>,
+}
+
+impl<'a, 'b, 'de> EnumAccess<'de> for EnumDeserializer<'a, 'b, 'de> {
+    type Error = Error;
+    type Variant = Self;
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self::Variant)> {
+        let index = self.deserializer.reader.read_varint()?;
+        let value = seed.deserialize(de::value::U32Deserializer::new(index))?;
+        Ok((value, self))
+    }
+}
+
+impl<'a, 'b, 'de> VariantAccess<'de> for EnumDeserializer<'a, 'b, 'de> {
+    type Error = Error;
+    fn unit_variant(self) -> Result<()> {
+        Ok(())
+    }
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        seed.deserialize(self.deserializer)
+    }
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value> {
+        de::Deserializer::deserialize_tuple(self.deserializer, len, visitor)
+    }
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        de::Deserializer::deserialize_struct(self.deserializer, "", fields, visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ser::to_bytes;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    #[test]
+    fn test_struct_round_trip() {
+        let point = Point { x: -3, y: 42 };
+        let bytes = to_bytes(&point).unwrap();
+        let decoded: Point = from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, point);
+    }
+
+    #[test]
+    fn test_vec_round_trip() {
+        let values: Vec<i32> = vec![1, -2, 3, -4];
+        let bytes = to_bytes(&values).unwrap();
+        let decoded: Vec<i32> = from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, values);
+    }
+
+    #[test]
+    fn test_option_round_trip() {
+        let value: Option<String> = Some("hi".to_string());
+        let bytes = to_bytes(&value).unwrap();
+        let decoded: Option<String> = from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, value);
+    }
+}
@@ -40,6 +40,30 @@ pub enum Error {
     #[error("unexpected end of file")]
     UnexpectedEof,
 
+    /// Nested-message recursion depth exceeded the configured limit.
+    #[error("recursion limit exceeded")]
+    RecursionLimitExceeded,
+
+    /// A length-delimited read would allocate more than the configured cap.
+    #[error("length limit exceeded: requested {requested} bytes, limit is {limit}")]
+    LengthLimitExceeded { requested: usize, limit: usize },
+
+    /// The cumulative size of length-prefixed reads over a `Reader`'s
+    /// lifetime (including its sub-readers) exceeded `ReaderLimits::max_total_bytes`,
+    /// or `OwnedReader::from_read_bounded` buffered more than its cap.
+    #[error("limit exceeded: cumulative reads reached {requested} bytes, limit is {limit}")]
+    LimitExceeded { requested: usize, limit: usize },
+
+    /// `read_map` decoded two entries to the same key, which can't be
+    /// represented in the `HashMap` it rebuilds.
+    #[error("duplicate map key")]
+    DuplicateMapKey,
+
+    /// `Writer::end_message_field` was called with a `MessageScope` that
+    /// wasn't the most recently opened, unclosed one.
+    #[error("unbalanced message field scope")]
+    UnbalancedMessageScope,
+
     /// IO error.
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
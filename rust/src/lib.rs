@@ -25,26 +25,40 @@
 //!                 let value = reader.read_string()?;
 //!                 assert_eq!(value, "hello");
 //!             }
-//!             _ => reader.skip_field(tag.wire_type)?,
+//!             _ => reader.skip_field(tag)?,
 //!         }
 //!     }
 //!     Ok(())
 //! }
 //! ```
 
+mod codec;
+mod compression;
+pub mod de;
 mod error;
 mod reader;
-mod registry;
+pub mod registry;
+pub mod ser;
 pub mod stream;
 mod types;
+mod unknown_fields;
 mod writer;
 
+pub use codec::{Decode, Encode};
 pub use error::{Error, Result};
-pub use reader::Reader;
-pub use registry::{Decoder, Encoder, Registry};
-pub use stream::{StreamReader, StreamWriter};
+/// Re-exported so `#[derive(CramberryMessage)]`'s expansion can submit an
+/// `inventory::submit!` registration without requiring `cramberry-derive`
+/// users to add `inventory` as a direct dependency themselves.
+pub use inventory;
+pub use reader::{OwnedReader, Reader, ReaderLimits};
+pub use registry::{global_registry, Decoder, Encoder, Registry};
+pub use stream::{
+    CodedInputStream, CodedOutputStream, MessageIndex, SeekableStreamReader, StreamHeader,
+    StreamReader, StreamWriter,
+};
 pub use types::{FieldTag, TypeId, WireType};
-pub use writer::Writer;
+pub use unknown_fields::{UnknownField, UnknownFields};
+pub use writer::{CanonicalWriter, MessageScope, Writer};
 
 /// Library version.
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -67,3 +81,36 @@ where
     let mut reader = Reader::new(data);
     decoder(&mut reader)
 }
+
+/// Encodes a value in canonical form: fields in ascending field-number
+/// order with no duplicates, via `Writer::canonical`. Equal values always
+/// produce identical bytes no matter what order `encoder` writes their
+/// fields in, which is what makes the result suitable for content-addressing
+/// or signing, and for byte-for-byte comparison against the Go runtime's
+/// canonical output.
+pub fn canonical_bytes<T, F>(value: &T, encoder: F) -> Result<Vec<u8>>
+where
+    F: FnOnce(&mut CanonicalWriter, &T) -> Result<()>,
+{
+    let mut writer = Writer::canonical();
+    encoder(&mut writer, value)?;
+    Ok(writer.finish()?.into_bytes())
+}
+
+/// Hashes a value's canonical encoding, so two logically-equal values always
+/// hash identically regardless of field write order.
+///
+/// The hash itself is `std::hash::Hash`'s default (SipHash) algorithm, not
+/// a cryptographic digest; pair `canonical_bytes` with an external hasher
+/// instead if a cryptographic digest is required.
+pub fn canonical_hash<T, F>(value: &T, encoder: F) -> Result<u64>
+where
+    F: FnOnce(&mut CanonicalWriter, &T) -> Result<()>,
+{
+    use std::hash::{Hash, Hasher};
+
+    let bytes = canonical_bytes(value, encoder)?;
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    Ok(hasher.finish())
+}
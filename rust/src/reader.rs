@@ -1,14 +1,37 @@
 //! Cramberry decoder.
 
+use std::cell::Cell;
+use std::rc::Rc;
+
+use crate::compression;
 use crate::error::{Error, Result};
 use crate::types::{
-    decode_compact_tag, zigzag_decode_32, zigzag_decode_64, FieldTag, WireType, END_MARKER,
+    compact_decode, decode_compact_tag, zigzag_decode_32, zigzag_decode_64, FieldTag, WireType,
+    END_MARKER,
 };
+use crate::unknown_fields::UnknownFields;
 
 /// Reader decodes Cramberry data from a binary buffer.
 pub struct Reader<'a> {
     buffer: &'a [u8],
     pos: usize,
+    limits: ReaderLimits,
+    /// Current nesting depth, shared with every sub-reader descended from
+    /// the same top-level `Reader` so the whole tree is bounded, not just
+    /// one branch of it.
+    depth: Rc<Cell<usize>>,
+    /// The depth this particular reader was created at. Restored on drop
+    /// so that returning to a sibling message doesn't keep charging the
+    /// depth of a message that has already finished decoding.
+    self_depth: usize,
+    /// Cumulative bytes admitted through `check_length` across every
+    /// reader descended from the same top-level `Reader`. Unlike `depth`
+    /// this is never restored on drop: it is a running total for the
+    /// whole decode, not a per-branch budget.
+    total_bytes: Rc<Cell<usize>>,
+    /// Present when unknown-field capture mode is enabled; `skip_field`
+    /// records into this instead of silently discarding the field.
+    unknown_fields: Option<UnknownFields<'a>>,
 }
 
 /// Maximum number of bytes for a varint-encoded uint64.
@@ -16,13 +39,174 @@ pub struct Reader<'a> {
 /// so we need ceil(64/7) = 10 bytes maximum.
 const MAX_VARINT_BYTES: usize = 10;
 
+/// Default maximum nesting depth for `sub_reader`, matching protobuf's
+/// `CodedInputStream.DEFAULT_RECURSION_LIMIT`.
+const DEFAULT_RECURSION_LIMIT: usize = 100;
+
+/// Default cap on any single length-prefixed allocation (64 MiB).
+const DEFAULT_MAX_LENGTH: usize = 64 * 1024 * 1024;
+
+/// Default cap on total bytes admitted through length-prefixed reads over
+/// the lifetime of a `Reader` and its sub-readers (256 MiB).
+const DEFAULT_MAX_TOTAL_BYTES: usize = 256 * 1024 * 1024;
+
+/// Limits `Reader` enforces against malicious or malformed input:
+/// `max_depth` bounds `sub_reader`/polymorphic nesting, `max_length`
+/// bounds any single length-prefixed allocation, and `max_total_bytes`
+/// bounds the cumulative size of all such allocations over the reader's
+/// lifetime (including its sub-readers). Defaults are generous enough
+/// that valid golden files decode unaffected; tighten them when decoding
+/// untrusted input.
+#[derive(Debug, Clone, Copy)]
+pub struct ReaderLimits {
+    pub max_depth: usize,
+    pub max_length: usize,
+    pub max_total_bytes: usize,
+}
+
+impl Default for ReaderLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: DEFAULT_RECURSION_LIMIT,
+            max_length: DEFAULT_MAX_LENGTH,
+            max_total_bytes: DEFAULT_MAX_TOTAL_BYTES,
+        }
+    }
+}
+
+/// Decodes a 32-bit varint from `data` without per-byte bounds checks.
+/// The caller must ensure `data` either holds `MAX_VARINT_BYTES` bytes or
+/// ends on a byte with its continuation bit clear. Returns the decoded
+/// value and the number of bytes consumed.
+fn decode_varint32_unrolled(data: &[u8]) -> Result<(u32, usize)> {
+    let mut result: u32 = 0;
+    let mut shift = 0;
+
+    for i in 0..data.len().min(MAX_VARINT_BYTES) {
+        let b = data[i];
+
+        // At the 5th byte (index 4), we've consumed 28 bits.
+        // The 5th byte can only contribute 4 more bits for a 32-bit value.
+        if i == 4 && (b & 0xf0) != 0 {
+            return Err(Error::VarintOverflow);
+        }
+
+        result |= ((b & 0x7f) as u32) << shift;
+        if b & 0x80 == 0 {
+            return Ok((result, i + 1));
+        }
+        shift += 7;
+    }
+
+    Err(Error::VarintOverflow)
+}
+
+/// Decodes a 64-bit varint from `data` without per-byte bounds checks.
+/// Same preconditions as `decode_varint32_unrolled`.
+fn decode_varint64_unrolled(data: &[u8]) -> Result<(u64, usize)> {
+    let mut result: u64 = 0;
+    let mut shift = 0;
+
+    for i in 0..data.len().min(MAX_VARINT_BYTES) {
+        let b = data[i];
+
+        // At the 10th byte (index 9), we've consumed 63 bits.
+        // The 10th byte can only contribute 1 more bit (bit 63 of uint64).
+        if i == 9 {
+            if b >= 0x80 {
+                return Err(Error::VarintOverflow);
+            }
+            if b > 1 {
+                return Err(Error::VarintOverflow);
+            }
+        }
+
+        result |= ((b & 0x7f) as u64) << shift;
+        if b & 0x80 == 0 {
+            return Ok((result, i + 1));
+        }
+        shift += 7;
+    }
+
+    Err(Error::VarintOverflow)
+}
+
 impl<'a> Reader<'a> {
     /// Creates a new reader from a byte slice.
     pub fn new(data: &'a [u8]) -> Self {
         Self {
             buffer: data,
             pos: 0,
+            limits: ReaderLimits::default(),
+            depth: Rc::new(Cell::new(0)),
+            self_depth: 0,
+            total_bytes: Rc::new(Cell::new(0)),
+            unknown_fields: None,
+        }
+    }
+
+    /// Sets the maximum nesting depth allowed for `sub_reader`.
+    pub fn set_recursion_limit(&mut self, limit: usize) {
+        self.limits.max_depth = limit;
+    }
+
+    /// Builder-style variant of `set_recursion_limit`.
+    pub fn with_recursion_limit(mut self, limit: usize) -> Self {
+        self.set_recursion_limit(limit);
+        self
+    }
+
+    /// Replaces the full set of input-hardening limits (depth, per-read
+    /// length, and cumulative bytes).
+    pub fn set_limits(&mut self, limits: ReaderLimits) {
+        self.limits = limits;
+    }
+
+    /// Builder-style variant of `set_limits`.
+    pub fn with_limits(mut self, limits: ReaderLimits) -> Self {
+        self.set_limits(limits);
+        self
+    }
+
+    /// Checks a length-prefixed read of `requested` bytes against both the
+    /// per-read cap and the cumulative budget before the caller allocates
+    /// anything sized from it.
+    fn check_length(&self, requested: usize) -> Result<()> {
+        if requested > self.limits.max_length {
+            return Err(Error::LengthLimitExceeded {
+                requested,
+                limit: self.limits.max_length,
+            });
+        }
+        let total = self.total_bytes.get() + requested;
+        if total > self.limits.max_total_bytes {
+            return Err(Error::LimitExceeded {
+                requested: total,
+                limit: self.limits.max_total_bytes,
+            });
         }
+        self.total_bytes.set(total);
+        Ok(())
+    }
+
+    /// Enables unknown-field capture mode: from this point on, `skip_field`
+    /// records the skipped tag instead of discarding it.
+    pub fn enable_unknown_field_capture(&mut self) {
+        self.unknown_fields.get_or_insert_with(UnknownFields::new);
+    }
+
+    /// Builder-style variant of `enable_unknown_field_capture`.
+    pub fn with_unknown_field_capture(mut self) -> Self {
+        self.enable_unknown_field_capture();
+        self
+    }
+
+    /// Takes the unknown fields captured so far, leaving capture mode
+    /// enabled but the collection empty.
+    pub fn take_unknown_fields(&mut self) -> UnknownFields<'a> {
+        self.unknown_fields
+            .replace(UnknownFields::new())
+            .unwrap_or_default()
     }
 
     /// Returns the current position in the buffer.
@@ -65,9 +249,38 @@ impl<'a> Reader<'a> {
     }
 
     /// Reads an unsigned varint (LEB128).
+    ///
+    /// Takes prost's two-tier approach: the overwhelmingly common
+    /// single-byte case is decoded with no loop and no `check_available`
+    /// call beyond the first byte. Multi-byte values are decoded from the
+    /// slice directly over a bounded window, avoiding a `check_available`
+    /// on every byte. Only falls back to the byte-at-a-time path when
+    /// fewer than `MAX_VARINT_BYTES` remain and a continuation byte could
+    /// run past the end of the buffer.
     /// For 32-bit values, this uses the same 10-byte limit as 64-bit for consistency,
     /// but the result is capped to 32 bits.
     pub fn read_varint(&mut self) -> Result<u32> {
+        self.check_available(1)?;
+        let first = self.buffer[self.pos];
+        if first < 0x80 {
+            self.pos += 1;
+            return Ok(first as u32);
+        }
+
+        let avail = self.remaining();
+        let window = avail.min(MAX_VARINT_BYTES);
+        if avail >= MAX_VARINT_BYTES || self.buffer[self.pos + window - 1] < 0x80 {
+            let (value, consumed) = decode_varint32_unrolled(&self.buffer[self.pos..self.pos + window])?;
+            self.pos += consumed;
+            return Ok(value);
+        }
+
+        self.read_varint_slow()
+    }
+
+    /// Byte-at-a-time fallback for `read_varint`, used only when fewer than
+    /// `MAX_VARINT_BYTES` remain in the buffer.
+    fn read_varint_slow(&mut self) -> Result<u32> {
         let mut result: u32 = 0;
         let mut shift = 0;
 
@@ -93,8 +306,33 @@ impl<'a> Reader<'a> {
     }
 
     /// Reads an unsigned 64-bit varint (LEB128).
+    ///
+    /// Uses the same fast path as `read_varint`: an early return for the
+    /// single-byte case, then an unrolled decode over a bounded window, falling back to
+    /// the byte-at-a-time loop only near the end of the buffer.
     /// Uses a maximum of 10 bytes, consistent with protobuf and Go implementation.
     pub fn read_varint64(&mut self) -> Result<u64> {
+        self.check_available(1)?;
+        let first = self.buffer[self.pos];
+        if first < 0x80 {
+            self.pos += 1;
+            return Ok(first as u64);
+        }
+
+        let avail = self.remaining();
+        let window = avail.min(MAX_VARINT_BYTES);
+        if avail >= MAX_VARINT_BYTES || self.buffer[self.pos + window - 1] < 0x80 {
+            let (value, consumed) = decode_varint64_unrolled(&self.buffer[self.pos..self.pos + window])?;
+            self.pos += consumed;
+            return Ok(value);
+        }
+
+        self.read_varint64_slow()
+    }
+
+    /// Byte-at-a-time fallback for `read_varint64`, used only when fewer
+    /// than `MAX_VARINT_BYTES` remain in the buffer.
+    fn read_varint64_slow(&mut self) -> Result<u64> {
         let mut result: u64 = 0;
         let mut shift = 0;
 
@@ -214,6 +452,19 @@ impl<'a> Reader<'a> {
         ]))
     }
 
+    /// Reads a SCALE-style compact variable-width integer (`WireType::Compact`).
+    /// See `compact_decode` for the format.
+    pub fn read_compact(&mut self) -> Result<u64> {
+        let remaining = &self.buffer[self.pos..];
+        if remaining.is_empty() {
+            return Err(Error::buffer_underflow(1, 0));
+        }
+
+        let (value, consumed) = compact_decode(remaining).ok_or(Error::VarintOverflow)?;
+        self.pos += consumed;
+        Ok(value)
+    }
+
     /// Reads a length-prefixed string.
     pub fn read_string(&mut self) -> Result<&'a str> {
         let length = self.read_varint()? as usize;
@@ -227,9 +478,25 @@ impl<'a> Reader<'a> {
         self.read_bytes(length)
     }
 
-    /// Skips a field based on its wire type.
-    pub fn skip_field(&mut self, wire_type: WireType) -> Result<()> {
-        match wire_type {
+    /// Reads a `Bytes` field written by `Writer::write_compressed_bytes_field`,
+    /// inflating it into an owned buffer.
+    ///
+    /// `max_inflated_size` bounds the decompressed output, so a crafted
+    /// payload can't be used as a decompression bomb.
+    pub fn read_compressed_bytes(&mut self, max_inflated_size: usize) -> Result<Vec<u8>> {
+        let payload = self.read_length_prefixed_bytes()?;
+        compression::decompress(payload, max_inflated_size)
+    }
+
+    /// Skips a field, given the tag that was just read for it.
+    ///
+    /// When unknown-field capture mode is enabled (see
+    /// `enable_unknown_field_capture`), the field number, wire type, and
+    /// exact raw bytes skipped are recorded instead of discarded, so they
+    /// can be re-emitted later with `Writer::write_unknown_fields`.
+    pub fn skip_field(&mut self, tag: FieldTag) -> Result<()> {
+        let start = self.pos;
+        match tag.wire_type {
             WireType::Varint | WireType::SVarint => {
                 self.read_varint64()?; // Use 64-bit to handle large varints
             }
@@ -246,17 +513,281 @@ impl<'a> Reader<'a> {
                 self.check_available(4)?;
                 self.pos += 4;
             }
+            WireType::Compact => {
+                self.read_compact()?;
+            }
+        }
+
+        if let Some(unknown) = self.unknown_fields.as_mut() {
+            unknown.push(tag.field_number, tag.wire_type, &self.buffer[start..self.pos]);
         }
+
         Ok(())
     }
 
     /// Creates a sub-reader for reading nested messages.
+    ///
+    /// The child inherits the parent's remaining recursion budget and its
+    /// cumulative byte budget, so both guards hold no matter how the
+    /// message tree is shaped. Returns `Error::RecursionLimitExceeded` if
+    /// descending would exceed `limits.max_depth`, or `Error::LimitExceeded`
+    /// / `Error::LengthLimitExceeded` if `length` would breach
+    /// `limits.max_total_bytes` / `limits.max_length`.
     pub fn sub_reader(&mut self, length: usize) -> Result<Reader<'a>> {
         self.check_available(length)?;
-        let sub = Reader::new(&self.buffer[self.pos..self.pos + length]);
+        self.check_length(length)?;
+
+        let child_depth = self.depth.get() + 1;
+        if child_depth > self.limits.max_depth {
+            return Err(Error::RecursionLimitExceeded);
+        }
+        self.depth.set(child_depth);
+
+        let sub = Reader {
+            buffer: &self.buffer[self.pos..self.pos + length],
+            pos: 0,
+            limits: self.limits,
+            depth: Rc::clone(&self.depth),
+            self_depth: child_depth,
+            total_bytes: Rc::clone(&self.total_bytes),
+            unknown_fields: None,
+        };
         self.pos += length;
         Ok(sub)
     }
+
+    /// Reads a packed repeated field of zigzag-encoded `int32` values from a
+    /// `Bytes`-wire-type field: a varint length prefix followed by a
+    /// back-to-back run of varints, consumed until exhausted.
+    pub fn read_packed_int32(&mut self) -> Result<Vec<i32>> {
+        let length = self.read_varint()? as usize;
+        let mut sub = self.sub_reader(length)?;
+        let mut values = Vec::new();
+        while sub.has_more() {
+            values.push(sub.read_svarint()?);
+        }
+        Ok(values)
+    }
+
+    /// Reads a packed repeated field of `uint64` values from a
+    /// `Bytes`-wire-type field.
+    pub fn read_packed_uint64(&mut self) -> Result<Vec<u64>> {
+        let length = self.read_varint()? as usize;
+        let mut sub = self.sub_reader(length)?;
+        let mut values = Vec::new();
+        while sub.has_more() {
+            values.push(sub.read_varint64()?);
+        }
+        Ok(values)
+    }
+
+    /// Reads a packed repeated field of zigzag-encoded `int64` values from a
+    /// `Bytes`-wire-type field.
+    pub fn read_packed_svarint64(&mut self) -> Result<Vec<i64>> {
+        let length = self.read_varint()? as usize;
+        let mut sub = self.sub_reader(length)?;
+        let mut values = Vec::new();
+        while sub.has_more() {
+            values.push(sub.read_svarint64()?);
+        }
+        Ok(values)
+    }
+
+    /// Reads a packed repeated field of fixed 32-bit values from a
+    /// `Bytes`-wire-type field: a varint length prefix followed by a
+    /// back-to-back run of 4-byte little-endian values. The length must be
+    /// an exact multiple of 4.
+    pub fn read_packed_fixed32(&mut self) -> Result<Vec<u32>> {
+        let length = self.read_varint()? as usize;
+        if length % 4 != 0 {
+            return Err(Error::custom(format!(
+                "packed fixed32 length {} is not a multiple of 4",
+                length
+            )));
+        }
+        let mut sub = self.sub_reader(length)?;
+        let mut values = Vec::with_capacity(length / 4);
+        while sub.has_more() {
+            values.push(sub.read_fixed32()?);
+        }
+        Ok(values)
+    }
+
+    /// Reads a packed repeated field of fixed 64-bit values from a
+    /// `Bytes`-wire-type field: a varint length prefix followed by a
+    /// back-to-back run of 8-byte little-endian values. The length must be
+    /// an exact multiple of 8.
+    pub fn read_packed_fixed64(&mut self) -> Result<Vec<u64>> {
+        let length = self.read_varint()? as usize;
+        if length % 8 != 0 {
+            return Err(Error::custom(format!(
+                "packed fixed64 length {} is not a multiple of 8",
+                length
+            )));
+        }
+        let mut sub = self.sub_reader(length)?;
+        let mut values = Vec::with_capacity(length / 8);
+        while sub.has_more() {
+            values.push(sub.read_fixed64()?);
+        }
+        Ok(values)
+    }
+
+    /// Reads a packed repeated field of 64-bit floats from a
+    /// `Bytes`-wire-type field: a varint length prefix followed by a
+    /// back-to-back run of 8-byte little-endian values. The length must be
+    /// an exact multiple of 8.
+    pub fn read_packed_float64(&mut self) -> Result<Vec<f64>> {
+        let length = self.read_varint()? as usize;
+        if length % 8 != 0 {
+            return Err(Error::custom(format!(
+                "packed float64 length {} is not a multiple of 8",
+                length
+            )));
+        }
+        let mut sub = self.sub_reader(length)?;
+        let mut values = Vec::with_capacity(length / 8);
+        while sub.has_more() {
+            values.push(sub.read_float64()?);
+        }
+        Ok(values)
+    }
+
+    /// Reads one map entry's key or value field, checking it carries the
+    /// expected field number before handing its bytes to `decode`.
+    fn read_map_entry_field<T>(
+        &mut self,
+        expected_field: u32,
+        mut decode: impl FnMut(&mut Reader<'a>) -> Result<T>,
+    ) -> Result<T> {
+        let tag = self.read_tag()?;
+        if tag.field_number != expected_field || tag.wire_type != WireType::Bytes {
+            return Err(Error::custom(format!(
+                "expected map/set field {}, got field {} wire type {:?}",
+                expected_field, tag.field_number, tag.wire_type
+            )));
+        }
+        let bytes = self.read_length_prefixed_bytes()?;
+        decode(&mut Reader::new(bytes))
+    }
+
+    /// Reads a tagged field holding an associative collection written by
+    /// `Writer::write_map_field`, rebuilding a `HashMap`.
+    ///
+    /// `decode_key`/`decode_value` each receive a fresh `Reader` scoped to
+    /// just that entry's encoded bytes. Returns `Error::DuplicateMapKey` if
+    /// two entries decode to the same key.
+    pub fn read_map<K, V>(
+        &mut self,
+        mut decode_key: impl FnMut(&mut Reader<'a>) -> Result<K>,
+        mut decode_value: impl FnMut(&mut Reader<'a>) -> Result<V>,
+    ) -> Result<std::collections::HashMap<K, V>>
+    where
+        K: std::hash::Hash + Eq,
+    {
+        let length = self.read_varint()? as usize;
+        let mut body = self.sub_reader(length)?;
+        let count = body.read_varint()? as usize;
+        let mut map = std::collections::HashMap::with_capacity(count);
+        for _ in 0..count {
+            let key = body.read_map_entry_field(1, &mut decode_key)?;
+            let value = body.read_map_entry_field(2, &mut decode_value)?;
+            if map.insert(key, value).is_some() {
+                return Err(Error::DuplicateMapKey);
+            }
+        }
+        Ok(map)
+    }
+
+    /// Reads a tagged field holding a set written by `Writer::write_set_field`,
+    /// rebuilding a `HashSet`.
+    ///
+    /// `decode_elem` receives a fresh `Reader` scoped to just that element's
+    /// encoded bytes. Elements that decode equal to one already seen are
+    /// silently deduplicated, matching `HashSet` semantics.
+    pub fn read_set<T>(
+        &mut self,
+        mut decode_elem: impl FnMut(&mut Reader<'a>) -> Result<T>,
+    ) -> Result<std::collections::HashSet<T>>
+    where
+        T: std::hash::Hash + Eq,
+    {
+        let length = self.read_varint()? as usize;
+        let mut body = self.sub_reader(length)?;
+        let count = body.read_varint()? as usize;
+        let mut set = std::collections::HashSet::with_capacity(count);
+        for _ in 0..count {
+            let elem = body.read_map_entry_field(1, &mut decode_elem)?;
+            set.insert(elem);
+        }
+        Ok(set)
+    }
+}
+
+impl Drop for Reader<'_> {
+    fn drop(&mut self) {
+        // Only sub-readers carry a non-zero self_depth; restore the shared
+        // counter to the parent's depth so sibling messages at the same
+        // level don't accumulate depth from their already-finished siblings.
+        if self.self_depth > 0 {
+            self.depth.set(self.self_depth - 1);
+        }
+    }
+}
+
+/// Chunk size used to pull data from an `std::io::Read` source into an
+/// `OwnedReader`'s internal buffer.
+const STREAM_READ_CHUNK: usize = 8 * 1024;
+
+/// An owned byte buffer filled incrementally from an `std::io::Read`
+/// source, so callers aren't limited to data that already lives in memory
+/// as a `&[u8]` (large golden files, data arriving over a socket, etc).
+///
+/// `Reader`'s zero-copy API (`read_string` returning `&str`, `read_bytes`
+/// returning `&[u8]`, ...) requires a buffer that outlives every borrow it
+/// hands out, so `OwnedReader` holds that buffer and lends a regular
+/// `Reader` over it via `reader()` rather than trying to decode directly
+/// from the `Read` source field-by-field.
+pub struct OwnedReader {
+    buffer: Vec<u8>,
+}
+
+impl OwnedReader {
+    /// Reads `r` to completion in `STREAM_READ_CHUNK`-sized pulls, growing
+    /// the internal buffer as more data arrives, until EOF. Bounded by
+    /// `ReaderLimits::default().max_total_bytes` so an unbounded or
+    /// malicious source (e.g. a socket that never closes) can't be used to
+    /// exhaust memory; use `from_read_bounded` to set a different cap.
+    pub fn from_read<R: std::io::Read>(r: &mut R) -> Result<Self> {
+        Self::from_read_bounded(r, ReaderLimits::default().max_total_bytes)
+    }
+
+    /// Like `from_read`, but with an explicit cap on total bytes buffered.
+    /// Returns `Error::LimitExceeded` if `r` has not reached EOF once
+    /// `max_total_bytes` have been read.
+    pub fn from_read_bounded<R: std::io::Read>(r: &mut R, max_total_bytes: usize) -> Result<Self> {
+        let mut buffer = Vec::new();
+        let mut chunk = [0u8; STREAM_READ_CHUNK];
+        loop {
+            let n = r.read(&mut chunk)?;
+            if n == 0 {
+                break;
+            }
+            if buffer.len() + n > max_total_bytes {
+                return Err(Error::LimitExceeded {
+                    requested: buffer.len() + n,
+                    limit: max_total_bytes,
+                });
+            }
+            buffer.extend_from_slice(&chunk[..n]);
+        }
+        Ok(Self { buffer })
+    }
+
+    /// Borrows a `Reader` over the buffered data.
+    pub fn reader(&self) -> Reader<'_> {
+        Reader::new(&self.buffer)
+    }
 }
 
 #[cfg(test)]
@@ -281,6 +812,33 @@ mod tests {
         assert_eq!(reader.read_varint().unwrap(), 300);
     }
 
+    #[test]
+    fn test_read_varint_fast_path_with_trailing_bytes() {
+        // At least MAX_VARINT_BYTES remain, so this exercises the unrolled
+        // decode path rather than the byte-at-a-time fallback.
+        let mut reader = Reader::new(&[0x80, 0x01, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+        assert_eq!(reader.read_varint().unwrap(), 128);
+        assert_eq!(reader.position(), 2);
+    }
+
+    #[test]
+    fn test_read_varint_near_buffer_end() {
+        // Fewer than MAX_VARINT_BYTES remain; must still decode correctly
+        // via the byte-at-a-time fallback. The trailing continuation byte
+        // keeps the bounded window from ending on a terminator.
+        let mut reader = Reader::new(&[0x80, 0x01, 0x80]);
+        assert_eq!(reader.read_varint().unwrap(), 128);
+    }
+
+    #[test]
+    fn test_read_varint64_fast_path_and_fallback() {
+        let mut reader = Reader::new(&[0xac, 0x02, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(reader.read_varint64().unwrap(), 300);
+
+        let mut reader = Reader::new(&[0xac, 0x02, 0x80]);
+        assert_eq!(reader.read_varint64().unwrap(), 300);
+    }
+
     #[test]
     fn test_read_svarint() {
         let mut reader = Reader::new(&[0]);
@@ -380,6 +938,243 @@ mod tests {
         assert!(!reader.has_more());
     }
 
+    #[test]
+    fn test_unknown_field_capture_and_round_trip() {
+        use crate::writer::Writer;
+
+        let mut writer = Writer::new();
+        writer.write_int32_field(1, 7).unwrap();
+        writer.write_string_field(99, "future field").unwrap();
+        let data = writer.into_bytes();
+
+        let mut reader = Reader::new(&data).with_unknown_field_capture();
+        let tag1 = reader.read_tag().unwrap();
+        assert_eq!(reader.read_int32().unwrap(), 7);
+        assert_eq!(tag1.field_number, 1);
+
+        let tag2 = reader.read_tag().unwrap();
+        reader.skip_field(tag2).unwrap();
+
+        let unknown = reader.take_unknown_fields();
+        assert_eq!(unknown.len(), 1);
+
+        let mut out = Writer::new();
+        out.write_int32_field(1, 7).unwrap();
+        out.write_unknown_fields(&unknown).unwrap();
+        assert_eq!(out.into_bytes(), data);
+    }
+
+    #[test]
+    fn test_skip_field_without_capture_discards() {
+        use crate::writer::Writer;
+
+        let mut writer = Writer::new();
+        writer.write_string_field(5, "ignored").unwrap();
+        let data = writer.into_bytes();
+
+        let mut reader = Reader::new(&data);
+        let tag = reader.read_tag().unwrap();
+        reader.skip_field(tag).unwrap();
+        assert!(!reader.has_more());
+    }
+
+    #[test]
+    fn test_compressed_bytes_field_round_trip() {
+        use crate::writer::Writer;
+
+        let payload = b"repeat repeat repeat repeat repeat repeat".repeat(4);
+
+        let mut writer = Writer::new();
+        writer.write_compressed_bytes_field(1, &payload).unwrap();
+        let data = writer.into_bytes();
+
+        let mut reader = Reader::new(&data);
+        let tag = reader.read_tag().unwrap();
+        assert_eq!(tag.field_number, 1);
+        assert_eq!(tag.wire_type, WireType::Bytes);
+
+        let decoded = reader.read_compressed_bytes(payload.len()).unwrap();
+        assert_eq!(decoded, payload);
+    }
+
+    #[test]
+    fn test_read_packed_int32() {
+        use crate::writer::Writer;
+
+        let mut payload = Writer::new();
+        payload.write_svarint(-1).unwrap();
+        payload.write_svarint(2).unwrap();
+        payload.write_svarint(300).unwrap();
+        let payload = payload.into_bytes();
+
+        let mut framed = Writer::new();
+        framed.write_length_prefixed_bytes(&payload).unwrap();
+
+        let data = framed.into_bytes();
+        let mut reader = Reader::new(&data);
+        assert_eq!(reader.read_packed_int32().unwrap(), vec![-1, 2, 300]);
+        assert!(!reader.has_more());
+    }
+
+    #[test]
+    fn test_read_packed_fixed32() {
+        use crate::writer::Writer;
+
+        let mut payload = Writer::new();
+        payload.write_fixed32(1).unwrap();
+        payload.write_fixed32(2).unwrap();
+        payload.write_fixed32(3).unwrap();
+        let payload = payload.into_bytes();
+
+        let mut framed = Writer::new();
+        framed.write_length_prefixed_bytes(&payload).unwrap();
+
+        let data = framed.into_bytes();
+        let mut reader = Reader::new(&data);
+        assert_eq!(reader.read_packed_fixed32().unwrap(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_read_packed_fixed32_bad_length() {
+        let mut framed = crate::writer::Writer::new();
+        framed.write_length_prefixed_bytes(&[0, 0, 0]).unwrap(); // 3 bytes, not a multiple of 4
+        let data = framed.into_bytes();
+        let mut reader = Reader::new(&data);
+        assert!(reader.read_packed_fixed32().is_err());
+    }
+
+    #[test]
+    fn test_write_packed_int32_round_trip() {
+        use crate::writer::Writer;
+
+        let mut writer = Writer::new();
+        writer.write_packed_int32(1, &[-1, 2, 300]).unwrap();
+        let data = writer.into_bytes();
+
+        let mut reader = Reader::new(&data);
+        let tag = reader.read_tag().unwrap();
+        assert_eq!(tag.field_number, 1);
+        assert_eq!(tag.wire_type, WireType::Bytes);
+        assert_eq!(reader.read_packed_int32().unwrap(), vec![-1, 2, 300]);
+        assert!(!reader.has_more());
+    }
+
+    #[test]
+    fn test_write_packed_uint64_round_trip() {
+        use crate::writer::Writer;
+
+        let mut writer = Writer::new();
+        writer.write_packed_uint64(1, &[0, 1, 300, u64::MAX]).unwrap();
+        let data = writer.into_bytes();
+
+        let mut reader = Reader::new(&data);
+        reader.read_tag().unwrap();
+        assert_eq!(
+            reader.read_packed_uint64().unwrap(),
+            vec![0, 1, 300, u64::MAX]
+        );
+    }
+
+    #[test]
+    fn test_write_packed_svarint64_round_trip() {
+        use crate::writer::Writer;
+
+        let mut writer = Writer::new();
+        writer
+            .write_packed_svarint64(1, &[-1, 2, i64::MIN, i64::MAX])
+            .unwrap();
+        let data = writer.into_bytes();
+
+        let mut reader = Reader::new(&data);
+        reader.read_tag().unwrap();
+        assert_eq!(
+            reader.read_packed_svarint64().unwrap(),
+            vec![-1, 2, i64::MIN, i64::MAX]
+        );
+    }
+
+    #[test]
+    fn test_write_packed_fixed32_round_trip() {
+        use crate::writer::Writer;
+
+        let mut writer = Writer::new();
+        writer.write_packed_fixed32(1, &[1, 2, 3]).unwrap();
+        let data = writer.into_bytes();
+
+        let mut reader = Reader::new(&data);
+        reader.read_tag().unwrap();
+        assert_eq!(reader.read_packed_fixed32().unwrap(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_write_packed_fixed64_round_trip() {
+        use crate::writer::Writer;
+
+        let mut writer = Writer::new();
+        writer.write_packed_fixed64(1, &[1, 2, u64::MAX]).unwrap();
+        let data = writer.into_bytes();
+
+        let mut reader = Reader::new(&data);
+        reader.read_tag().unwrap();
+        assert_eq!(reader.read_packed_fixed64().unwrap(), vec![1, 2, u64::MAX]);
+    }
+
+    #[test]
+    fn test_write_compact_field_round_trip() {
+        use crate::writer::Writer;
+
+        for &value in &[0u64, 63, 64, 0x3fff, 0x4000, 0x3fff_ffff, 0x4000_0000, u64::MAX] {
+            let mut writer = Writer::new();
+            writer.write_compact_field(3, value).unwrap();
+            let data = writer.into_bytes();
+
+            let mut reader = Reader::new(&data);
+            let tag = reader.read_tag().unwrap();
+            assert_eq!(tag.field_number, 3);
+            assert_eq!(tag.wire_type, WireType::Compact);
+            assert_eq!(reader.read_compact().unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn test_skip_field_compact() {
+        use crate::writer::Writer;
+
+        let mut writer = Writer::new();
+        writer.write_compact_field(1, 0x1234_5678_9abc).unwrap();
+        writer.write_uint32_field(2, 7).unwrap();
+        let data = writer.into_bytes();
+
+        let mut reader = Reader::new(&data);
+        let tag = reader.read_tag().unwrap();
+        reader.skip_field(tag).unwrap();
+        let tag = reader.read_tag().unwrap();
+        assert_eq!(tag.field_number, 2);
+        assert_eq!(reader.read_varint().unwrap(), 7);
+    }
+
+    #[test]
+    fn test_sub_reader_recursion_limit() {
+        let mut reader = Reader::new(&[]).with_recursion_limit(2);
+
+        let mut level1 = reader.sub_reader(0).unwrap();
+        let mut level2 = level1.sub_reader(0).unwrap();
+        let err = level2.sub_reader(0).unwrap_err();
+        assert!(matches!(err, Error::RecursionLimitExceeded));
+    }
+
+    #[test]
+    fn test_sub_reader_depth_restored_for_siblings() {
+        let mut reader = Reader::new(&[]).with_recursion_limit(1);
+
+        {
+            let _sibling = reader.sub_reader(0).unwrap();
+        }
+        // The first sibling's sub-reader was dropped, so depth should be
+        // back to 0 and a second sibling at the same level is allowed.
+        assert!(reader.sub_reader(0).is_ok());
+    }
+
     #[test]
     fn test_peek_end_marker() {
         let mut reader = Reader::new(&[0x10, END_MARKER]);
@@ -398,4 +1193,201 @@ mod tests {
         let end_tag = reader.read_tag().unwrap();
         assert!(Reader::is_end_marker(&end_tag));
     }
+
+    #[test]
+    fn test_owned_reader_from_read_matches_in_memory() {
+        let mut writer = crate::writer::Writer::new();
+        writer.write_int32_field(1, 42).unwrap();
+        writer.write_string_field(2, "hello").unwrap();
+        let data = writer.into_bytes();
+
+        let mut source = &data[..];
+        let owned = OwnedReader::from_read(&mut source).unwrap();
+        let mut reader = owned.reader();
+
+        let tag = reader.read_tag().unwrap();
+        assert_eq!(tag.field_number, 1);
+        assert_eq!(reader.read_int32().unwrap(), 42);
+
+        let tag = reader.read_tag().unwrap();
+        assert_eq!(tag.field_number, 2);
+        assert_eq!(reader.read_string().unwrap(), "hello");
+    }
+
+    #[test]
+    fn test_sub_reader_respects_max_length() {
+        let data = vec![0u8; 100];
+        let mut reader = Reader::new(&data).with_limits(ReaderLimits {
+            max_depth: DEFAULT_RECURSION_LIMIT,
+            max_length: 10,
+            max_total_bytes: DEFAULT_MAX_TOTAL_BYTES,
+        });
+        let err = reader.sub_reader(50).unwrap_err();
+        assert!(matches!(err, Error::LengthLimitExceeded { requested: 50, limit: 10 }));
+    }
+
+    #[test]
+    fn test_sub_reader_respects_max_total_bytes() {
+        let data = vec![0u8; 100];
+        let mut reader = Reader::new(&data).with_limits(ReaderLimits {
+            max_depth: DEFAULT_RECURSION_LIMIT,
+            max_length: DEFAULT_MAX_LENGTH,
+            max_total_bytes: 30,
+        });
+        assert!(reader.sub_reader(20).is_ok());
+        let err = reader.sub_reader(20).unwrap_err();
+        assert!(matches!(err, Error::LimitExceeded { .. }));
+    }
+
+    #[test]
+    fn test_sub_reader_total_bytes_shared_with_children() {
+        let data = vec![0u8; 100];
+        let mut reader = Reader::new(&data).with_limits(ReaderLimits {
+            max_depth: DEFAULT_RECURSION_LIMIT,
+            max_length: DEFAULT_MAX_LENGTH,
+            max_total_bytes: 30,
+        });
+        let mut child = reader.sub_reader(20).unwrap();
+        // The child shares the same cumulative budget as its parent, so a
+        // further 20-byte sub-read (40 total) breaches the 30-byte cap.
+        let err = child.sub_reader(20).unwrap_err();
+        assert!(matches!(err, Error::LimitExceeded { .. }));
+    }
+
+    #[test]
+    fn test_owned_reader_from_read_bounded_rejects_oversized_source() {
+        let data = vec![0u8; 100];
+        let mut source = &data[..];
+        let err = OwnedReader::from_read_bounded(&mut source, 10).unwrap_err();
+        assert!(matches!(err, Error::LimitExceeded { .. }));
+    }
+
+    #[test]
+    fn test_map_field_round_trip() {
+        use crate::writer::Writer;
+        use std::collections::HashMap;
+
+        let mut map = HashMap::new();
+        map.insert("a".to_string(), 1i32);
+        map.insert("b".to_string(), 2i32);
+        map.insert("c".to_string(), 3i32);
+
+        let mut writer = Writer::new();
+        writer
+            .write_map_field(
+                1,
+                &map,
+                |w, k| w.write_string(k),
+                |w, v| w.write_svarint(*v),
+            )
+            .unwrap();
+        let data = writer.into_bytes();
+
+        let mut reader = Reader::new(&data);
+        reader.read_tag().unwrap();
+        let decoded = reader
+            .read_map(
+                |r| Ok(r.read_string()?.to_string()),
+                |r| r.read_svarint(),
+            )
+            .unwrap();
+        assert_eq!(decoded, map);
+    }
+
+    #[test]
+    fn test_map_field_is_sorted_by_encoded_key() {
+        use crate::writer::Writer;
+        use std::collections::HashMap;
+
+        let mut map = HashMap::new();
+        map.insert("zzz".to_string(), 1i32);
+        map.insert("aaa".to_string(), 2i32);
+
+        let mut first = Writer::new();
+        first
+            .write_map_field(1, &map, |w, k| w.write_string(k), |w, v| w.write_svarint(*v))
+            .unwrap();
+
+        let mut second = Writer::new();
+        second
+            .write_map_field(1, &map, |w, k| w.write_string(k), |w, v| w.write_svarint(*v))
+            .unwrap();
+
+        // Iteration order over a HashMap is unspecified, but both encodes
+        // must agree on the byte-sorted entry order.
+        assert_eq!(first.into_bytes(), second.into_bytes());
+    }
+
+    #[test]
+    fn test_read_map_rejects_duplicate_key() {
+        use crate::writer::Writer;
+
+        // Hand-build a map body with the same key encoded twice; the
+        // public `write_map_field` API can't produce this since its input
+        // is already a `HashMap`.
+        let mut entry_a = Writer::new();
+        entry_a.write_bytes_field(1, b"k").unwrap();
+        entry_a.write_bytes_field(2, b"1").unwrap();
+
+        let mut entry_b = Writer::new();
+        entry_b.write_bytes_field(1, b"k").unwrap();
+        entry_b.write_bytes_field(2, b"2").unwrap();
+
+        let mut body = Writer::new();
+        body.write_varint(2).unwrap();
+        body.write_bytes(entry_a.as_bytes()).unwrap();
+        body.write_bytes(entry_b.as_bytes()).unwrap();
+
+        let mut framed = Writer::new();
+        framed.write_bytes_field(1, body.as_bytes()).unwrap();
+        let data = framed.into_bytes();
+
+        let mut reader = Reader::new(&data);
+        reader.read_tag().unwrap();
+        let err = reader
+            .read_map(
+                |r| Ok(r.read_bytes(1)?.to_vec()),
+                |r| Ok(r.read_bytes(1)?.to_vec()),
+            )
+            .unwrap_err();
+        assert!(matches!(err, Error::DuplicateMapKey));
+    }
+
+    #[test]
+    fn test_set_field_round_trip() {
+        use crate::writer::Writer;
+        use std::collections::HashSet;
+
+        let mut set = HashSet::new();
+        set.insert(10i32);
+        set.insert(20i32);
+        set.insert(30i32);
+
+        let mut writer = Writer::new();
+        writer
+            .write_set_field(1, &set, |w, v| w.write_svarint(*v))
+            .unwrap();
+        let data = writer.into_bytes();
+
+        let mut reader = Reader::new(&data);
+        reader.read_tag().unwrap();
+        let decoded = reader.read_set(|r| r.read_svarint()).unwrap();
+        assert_eq!(decoded, set);
+    }
+
+    #[test]
+    fn test_owned_reader_spans_chunk_boundary() {
+        // Larger than STREAM_READ_CHUNK so `from_read` must loop.
+        let big_string = "x".repeat(STREAM_READ_CHUNK * 2);
+        let mut writer = crate::writer::Writer::new();
+        writer.write_string_field(1, &big_string).unwrap();
+        let data = writer.into_bytes();
+
+        let mut source = &data[..];
+        let owned = OwnedReader::from_read(&mut source).unwrap();
+        let mut reader = owned.reader();
+        let tag = reader.read_tag().unwrap();
+        assert_eq!(tag.field_number, 1);
+        assert_eq!(reader.read_string().unwrap(), big_string);
+    }
 }
@@ -148,6 +148,20 @@ impl Registry {
         self.register_with_id_inner(&mut inner, name, type_id, encoder, decoder)
     }
 
+    /// Registers a type that implements `Encode`/`Decode` with automatic ID
+    /// assignment, building the `Encoder<T>`/`Decoder<T>` fn pointers
+    /// `register` expects from `T`'s trait impls.
+    ///
+    /// Lets a composable `Encode`/`Decode` type (including a blanket impl
+    /// like `Vec<T>` or `Option<T>`) plug into the registry without a
+    /// hand-written encode/decode fn of its own.
+    pub fn register_type<T>(&self, name: &str) -> TypeId
+    where
+        T: crate::codec::Encode + crate::codec::Decode + 'static + Send + Sync,
+    {
+        self.register(name, |writer, value: &T| value.encode(writer), |reader| T::decode(reader))
+    }
+
     /// Gets the type ID for a registered type name.
     /// Thread-safe: acquires read lock.
     pub fn get_type_id(&self, name: &str) -> Result<TypeId> {
@@ -263,6 +277,32 @@ impl Default for Registry {
     }
 }
 
+/// A single type's registration thunk, submitted via `inventory::submit!`
+/// by `#[derive(CramberryMessage)]`-generated code so `encode_polymorphic`/
+/// `decode_polymorphic` work against [`global_registry`] without a manual
+/// `register` call.
+pub struct Registration {
+    pub name: &'static str,
+    pub register: fn(&Registry),
+}
+
+inventory::collect!(Registration);
+
+static GLOBAL_REGISTRY: std::sync::OnceLock<Registry> = std::sync::OnceLock::new();
+
+/// The process-wide `Registry`, populated on first access from every
+/// `Registration` collected by `inventory` across the binary, including
+/// every `#[derive(CramberryMessage)]` type linked in.
+pub fn global_registry() -> &'static Registry {
+    GLOBAL_REGISTRY.get_or_init(|| {
+        let registry = Registry::new();
+        for registration in inventory::iter::<Registration> {
+            (registration.register)(&registry);
+        }
+        registry
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -292,7 +332,7 @@ mod tests {
             match tag.field_number {
                 1 => value = reader.read_int32()?,
                 2 => name = reader.read_string()?.to_string(),
-                _ => reader.skip_field(tag.wire_type)?,
+                _ => reader.skip_field(tag)?,
             }
         }
 
@@ -335,6 +375,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_register_type_uses_encode_decode_impl() {
+        let registry = Registry::new();
+        let type_id = registry.register_type::<i32>("i32");
+
+        assert_eq!(type_id, 128);
+        assert!(registry.is_registered("i32"));
+
+        let mut writer = Writer::new();
+        registry
+            .encode_polymorphic(&mut writer, 1, "i32", &42i32)
+            .unwrap();
+        let data = writer.into_bytes();
+
+        let mut reader = Reader::new(&data);
+        let tag = reader.read_tag().unwrap();
+        assert_eq!(tag.field_number, 1);
+        let length = reader.read_varint().unwrap() as usize;
+        let mut type_ref = reader.sub_reader(length).unwrap();
+
+        let (name, value) = registry.decode_polymorphic(&mut type_ref).unwrap();
+        assert_eq!(name, "i32");
+        assert_eq!(*value.downcast::<i32>().unwrap(), 42);
+    }
+
     #[test]
     fn test_register_or_get() {
         let registry = Registry::new();
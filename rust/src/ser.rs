@@ -0,0 +1,373 @@
+//! `serde::Serializer` backend over `Writer`.
+//!
+//! Maps serde's data model onto the existing wire types: `i8`/`i16`/`i32`
+//! use `write_svarint`, `i64` uses `write_svarint64`, `u8`/`u16`/`u32` use
+//! `write_varint`, `u64` uses `write_varint64`, `f32`/`f64` use
+//! Fixed32/Fixed64, and strings/byte slices use the Bytes wire type.
+//! Sequences and maps emit a leading element-count varint followed by
+//! each element; structs do the same with a leading field-count varint
+//! followed by one tagged field per declared field, numbered from 1 in
+//! declaration order (serde only gives a `Serializer` field *names*, not
+//! the numeric tags Cramberry's wire format needs).
+
+use std::cell::Cell;
+
+use serde::ser::{
+    self, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
+    SerializeTupleStruct, SerializeTupleVariant,
+};
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+use crate::types::WireType;
+use crate::writer::Writer;
+
+impl ser::Error for Error {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Error::custom(msg.to_string())
+    }
+}
+
+/// Serializes `value` into a fresh `Writer` and returns the encoded bytes.
+pub fn to_bytes<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>> {
+    let mut writer = Writer::new();
+    to_writer(&mut writer, value)?;
+    Ok(writer.into_bytes())
+}
+
+/// Serializes `value` by appending to an existing `Writer`.
+pub fn to_writer<T: Serialize + ?Sized>(writer: &mut Writer, value: &T) -> Result<()> {
+    value.serialize(&mut Serializer::new(writer))
+}
+
+/// Serializer that writes values to a `Writer`.
+///
+/// Tracks the wire type of the value it most recently wrote so that
+/// `StructSerializer` (which must write a field's tag *before* its value)
+/// can recover it after a scratch-buffer encode.
+pub struct Serializer<'w> {
+    writer: &'w mut Writer,
+    last_wire_type: Cell<Option<WireType>>,
+}
+
+impl<'w> Serializer<'w> {
+    fn new(writer: &'w mut Writer) -> Self {
+        Self {
+            writer,
+            last_wire_type: Cell::new(None),
+        }
+    }
+
+    fn mark(&self, wire_type: WireType) {
+        self.last_wire_type.set(Some(wire_type));
+    }
+}
+
+impl<'w, 'a> ser::Serializer for &'a mut Serializer<'w> {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = SeqSerializer<'w, 'a>;
+    type SerializeTuple = SeqSerializer<'w, 'a>;
+    type SerializeTupleStruct = SeqSerializer<'w, 'a>;
+    type SerializeTupleVariant = SeqSerializer<'w, 'a>;
+    type SerializeMap = SeqSerializer<'w, 'a>;
+    type SerializeStruct = StructSerializer<'w, 'a>;
+    type SerializeStructVariant = StructSerializer<'w, 'a>;
+
+    fn serialize_bool(self, v: bool) -> Result<()> {
+        self.mark(WireType::Varint);
+        self.writer.write_bool(v)
+    }
+    fn serialize_i8(self, v: i8) -> Result<()> {
+        self.mark(WireType::SVarint);
+        self.writer.write_svarint(v as i32)
+    }
+    fn serialize_i16(self, v: i16) -> Result<()> {
+        self.mark(WireType::SVarint);
+        self.writer.write_svarint(v as i32)
+    }
+    fn serialize_i32(self, v: i32) -> Result<()> {
+        self.mark(WireType::SVarint);
+        self.writer.write_svarint(v)
+    }
+    fn serialize_i64(self, v: i64) -> Result<()> {
+        self.mark(WireType::SVarint);
+        self.writer.write_svarint64(v)
+    }
+    fn serialize_u8(self, v: u8) -> Result<()> {
+        self.mark(WireType::Varint);
+        self.writer.write_varint(v as u32)
+    }
+    fn serialize_u16(self, v: u16) -> Result<()> {
+        self.mark(WireType::Varint);
+        self.writer.write_varint(v as u32)
+    }
+    fn serialize_u32(self, v: u32) -> Result<()> {
+        self.mark(WireType::Varint);
+        self.writer.write_varint(v)
+    }
+    fn serialize_u64(self, v: u64) -> Result<()> {
+        self.mark(WireType::Varint);
+        self.writer.write_varint64(v)
+    }
+    fn serialize_f32(self, v: f32) -> Result<()> {
+        self.mark(WireType::Fixed32);
+        self.writer.write_float32(v)
+    }
+    fn serialize_f64(self, v: f64) -> Result<()> {
+        self.mark(WireType::Fixed64);
+        self.writer.write_float64(v)
+    }
+    fn serialize_char(self, v: char) -> Result<()> {
+        let mut buf = [0u8; 4];
+        self.serialize_str(v.encode_utf8(&mut buf))
+    }
+    fn serialize_str(self, v: &str) -> Result<()> {
+        self.mark(WireType::Bytes);
+        self.writer.write_string(v)
+    }
+    fn serialize_bytes(self, v: &[u8]) -> Result<()> {
+        self.mark(WireType::Bytes);
+        self.writer.write_length_prefixed_bytes(v)
+    }
+    fn serialize_none(self) -> Result<()> {
+        self.mark(WireType::Varint);
+        self.writer.write_bool(false)
+    }
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<()> {
+        self.mark(WireType::Varint);
+        self.writer.write_bool(true)?;
+        value.serialize(self)
+    }
+    fn serialize_unit(self) -> Result<()> {
+        Ok(())
+    }
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
+        Ok(())
+    }
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<()> {
+        self.mark(WireType::Varint);
+        self.writer.write_varint(variant_index)
+    }
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.mark(WireType::Bytes);
+        self.writer.write_varint(variant_index)?;
+        value.serialize(self)
+    }
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq> {
+        let len = len.ok_or_else(|| Error::custom("serialize_seq requires a known length"))?;
+        self.mark(WireType::Bytes);
+        self.writer.write_varint(len as u32)?;
+        Ok(SeqSerializer { serializer: self })
+    }
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple> {
+        self.serialize_seq(Some(len))
+    }
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        self.serialize_seq(Some(len))
+    }
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        self.mark(WireType::Bytes);
+        self.writer.write_varint(variant_index)?;
+        self.writer.write_varint(len as u32)?;
+        Ok(SeqSerializer { serializer: self })
+    }
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap> {
+        let len = len.ok_or_else(|| Error::custom("serialize_map requires a known length"))?;
+        self.mark(WireType::Bytes);
+        self.writer.write_varint(len as u32)?;
+        Ok(SeqSerializer { serializer: self })
+    }
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Self::SerializeStruct> {
+        self.mark(WireType::Bytes);
+        self.writer.write_varint(len as u32)?;
+        Ok(StructSerializer {
+            serializer: self,
+            field_number: 0,
+        })
+    }
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        self.mark(WireType::Bytes);
+        self.writer.write_varint(variant_index)?;
+        self.writer.write_varint(len as u32)?;
+        Ok(StructSerializer {
+            serializer: self,
+            field_number: 0,
+        })
+    }
+}
+
+/// Drives `SerializeSeq`/`SerializeTuple`/`SerializeMap` by serializing
+/// each element back through the same `Serializer`.
+pub struct SeqSerializer<'w, 'a> {
+    serializer: &'a mut Serializer<'w>,
+}
+
+impl<'w, 'a> SerializeSeq for SeqSerializer<'w, 'a> {
+    type Ok = ();
+    type Error = Error;
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        value.serialize(&mut *self.serializer)
+    }
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl<'w, 'a> SerializeTuple for SeqSerializer<'w, 'a> {
+    type Ok = ();
+    type Error = Error;
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        SerializeSeq::serialize_element(self, value)
+    }
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl<'w, 'a> SerializeTupleStruct for SeqSerializer<'w, 'a> {
+    type Ok = ();
+    type Error = Error;
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        SerializeSeq::serialize_element(self, value)
+    }
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl<'w, 'a> SerializeTupleVariant for SeqSerializer<'w, 'a> {
+    type Ok = ();
+    type Error = Error;
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        SerializeSeq::serialize_element(self, value)
+    }
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl<'w, 'a> SerializeMap for SeqSerializer<'w, 'a> {
+    type Ok = ();
+    type Error = Error;
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<()> {
+        key.serialize(&mut *self.serializer)
+    }
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        value.serialize(&mut *self.serializer)
+    }
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Drives `SerializeStruct`/`SerializeStructVariant`. The field count was
+/// already written by `serialize_struct`, so this only assigns each
+/// declared field the next sequential field number (starting at 1) and
+/// writes it as a tagged value.
+pub struct StructSerializer<'w, 'a> {
+    serializer: &'a mut Serializer<'w>,
+    field_number: u32,
+}
+
+impl<'w, 'a> SerializeStruct for StructSerializer<'w, 'a> {
+    type Ok = ();
+    type Error = Error;
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.field_number += 1;
+        // `Writer` needs the field's wire type before the value, but only
+        // the value's own `serialize_*` call knows it. Encode into a
+        // scratch writer first, read back which wire type it picked, then
+        // write the real tag followed by the already-encoded bytes.
+        let mut scratch_writer = Writer::new();
+        let mut scratch = Serializer::new(&mut scratch_writer);
+        value.serialize(&mut scratch)?;
+        let wire_type = scratch.last_wire_type.get().unwrap_or(WireType::Bytes);
+        self.serializer.writer.write_tag(self.field_number, wire_type)?;
+        self.serializer.writer.write_bytes(scratch_writer.as_bytes())?;
+        Ok(())
+    }
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl<'w, 'a> SerializeStructVariant for StructSerializer<'w, 'a> {
+    type Ok = ();
+    type Error = Error;
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        SerializeStruct::serialize_field(self, key, value)
+    }
+    fn end(self) -> Result<()> {
+        SerializeStruct::end(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize_scalars() {
+        assert_eq!(to_bytes(&42i32).unwrap(), {
+            let mut w = Writer::new();
+            w.write_svarint(42).unwrap();
+            w.into_bytes()
+        });
+        assert_eq!(to_bytes(&"hi").unwrap(), {
+            let mut w = Writer::new();
+            w.write_string("hi").unwrap();
+            w.into_bytes()
+        });
+    }
+
+    #[test]
+    fn test_serialize_seq_has_count_prefix() {
+        let bytes = to_bytes(&vec![1i32, 2, 3]).unwrap();
+        let mut reader = crate::reader::Reader::new(&bytes);
+        assert_eq!(reader.read_varint().unwrap(), 3);
+    }
+}
@@ -49,9 +49,14 @@
 //! }
 //! ```
 
-use std::io::{BufReader, BufWriter, Read, Write};
+use std::io::{BufRead, BufReader, BufWriter, IoSlice, Read, Seek, SeekFrom, Write};
 
 use crate::error::{Error, Result};
+use crate::types::{
+    decode_compact_tag, zigzag_decode_32, zigzag_decode_64, zigzag_encode_64, FieldTag, WireType,
+    END_MARKER, MAX_COMPACT_TAG_LEN, TAG_EXTENDED_BIT, TAG_FIELD_NUM_SHIFT, TAG_WIRE_TYPE_MASK,
+    TAG_WIRE_TYPE_SHIFT,
+};
 
 /// Default buffer capacity for stream readers/writers.
 const DEFAULT_BUFFER_CAPACITY: usize = 8192;
@@ -59,12 +64,87 @@ const DEFAULT_BUFFER_CAPACITY: usize = 8192;
 /// Maximum message size allowed in streaming mode (64 MB by default).
 const DEFAULT_MAX_MESSAGE_SIZE: usize = 64 * 1024 * 1024;
 
+/// Default cap on a single length-delimited allocation made while decoding
+/// fields directly off a stream, matching protobuf's
+/// `CodedInputStream.READ_RAW_BYTES_MAX_ALLOC`. This is deliberately much
+/// smaller than `DEFAULT_MAX_MESSAGE_SIZE`: it bounds one string/bytes/
+/// sub-message read, not the whole top-level message.
+const DEFAULT_MAX_ALLOC_SIZE: usize = 10_000_000;
+
+/// Initial chunk size for `StreamReader::read_incremental`'s geometric
+/// growth, matching `OwnedReader`'s `STREAM_READ_CHUNK`.
+const INCREMENTAL_READ_INITIAL_CHUNK: usize = 8 * 1024;
+
+/// Maximum encoded length of a varint length prefix (10 bytes for a u64).
+const MAX_VARINT_LEN: usize = 10;
+
+/// Fixed sequence at the start of a framed stream, ahead of the negotiated
+/// `StreamHeader`. Lets `StreamReader::read_header` reject a stream that
+/// isn't cramberry framing at all with a descriptive error, rather than
+/// misinterpreting arbitrary leading bytes as a version varint.
+const STREAM_MAGIC: [u8; 4] = *b"CRMB";
+
+/// Default ceiling `StreamReader::read_header` enforces on the version it
+/// reads, when the reader hasn't been given a tighter one via
+/// `set_max_version`. `u32::MAX` accepts any version a writer could encode,
+/// i.e. "no ceiling" by default.
+const DEFAULT_MAX_VERSION: u32 = u32::MAX;
+
+/// A stream header negotiated between `StreamWriter::write_header` and
+/// `StreamReader::read_header`, following the protocol-versioning model
+/// used by e.g. grin's serialization layer: a version number carried on
+/// the wire so a reader and writer agree on how the rest of the stream is
+/// encoded, instead of assuming they were built from the same crate
+/// version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamHeader {
+    /// Protocol version the remainder of the stream was written with.
+    pub version: u32,
+    /// Reserved bitflags for future features (checksums, compression, ...).
+    /// Unrecognized bits are passed through rather than rejected, so an
+    /// older reader isn't broken by flags introduced by a newer writer.
+    pub flags: u32,
+}
+
+/// Encodes `value` as an unsigned varint (LEB128) into `buf`, returning the
+/// number of bytes written. Shared by `StreamWriter::write_varint` and the
+/// vectored write paths, which need the encoded prefix as a byte slice
+/// rather than written straight to a `Write`.
+fn encode_varint(buf: &mut [u8; MAX_VARINT_LEN], mut value: u64) -> usize {
+    let mut i = 0;
+    while value > 0x7f {
+        buf[i] = (value as u8 & 0x7f) | 0x80;
+        value >>= 7;
+        i += 1;
+    }
+    buf[i] = value as u8;
+    i + 1
+}
+
+/// Stable-Rust equivalent of the nightly-only `Write::write_all_vectored`:
+/// repeatedly calls `write_vectored`, advancing past however many bytes
+/// each call accepts, until every slice is fully written.
+fn write_all_vectored<W: Write>(writer: &mut W, mut bufs: &mut [IoSlice<'_>]) -> Result<()> {
+    while bufs.iter().any(|b| !b.is_empty()) {
+        let n = writer.write_vectored(bufs).map_err(Error::from)?;
+        if n == 0 {
+            return Err(Error::Io(std::io::Error::new(
+                std::io::ErrorKind::WriteZero,
+                "failed to write whole buffer",
+            )));
+        }
+        IoSlice::advance_slices(&mut bufs, n);
+    }
+    Ok(())
+}
+
 /// StreamWriter writes length-delimited messages to a byte stream.
 ///
 /// Messages are written as [length: varint][data: bytes], where length
 /// is the number of bytes in the message data.
 pub struct StreamWriter<W: Write> {
     inner: BufWriter<W>,
+    capacity: usize,
 }
 
 impl<W: Write> StreamWriter<W> {
@@ -77,18 +157,88 @@ impl<W: Write> StreamWriter<W> {
     pub fn with_capacity(capacity: usize, writer: W) -> Self {
         Self {
             inner: BufWriter::with_capacity(capacity, writer),
+            capacity,
         }
     }
 
+    /// Writes a self-describing stream header: the fixed `STREAM_MAGIC`
+    /// sequence followed by `version` as a varint, with flags set to 0.
+    ///
+    /// Call this once, before any `write_message` calls, if the reader on
+    /// the other end may need to negotiate a protocol version (e.g. to stay
+    /// compatible with streams written by older or newer crate versions).
+    /// Pair with `StreamReader::read_header`.
+    pub fn write_header(&mut self, version: u32) -> Result<()> {
+        self.write_header_with_flags(version, 0)
+    }
+
+    /// Like `write_header`, but also carries a varint flags word for
+    /// future features (e.g. checksums or compression) that a reader can
+    /// inspect without needing a new protocol version.
+    pub fn write_header_with_flags(&mut self, version: u32, flags: u32) -> Result<()> {
+        self.inner.write_all(&STREAM_MAGIC).map_err(Error::from)?;
+        self.write_varint(version as u64)?;
+        self.write_varint(flags as u64)?;
+        Ok(())
+    }
+
     /// Writes a length-delimited message.
     ///
-    /// The message is prefixed with its length as a varint.
+    /// The message is prefixed with its length as a varint. Once `data` is
+    /// larger than the internal buffer's capacity — the point at which
+    /// `BufWriter` itself would bypass buffering and write `data` directly
+    /// — this instead switches to `write_message_vectored`, so the prefix
+    /// and payload reach the underlying writer together rather than as two
+    /// separate writes.
     pub fn write_message(&mut self, data: &[u8]) -> Result<()> {
+        if data.len() > self.capacity {
+            return self.write_message_vectored(data);
+        }
         self.write_varint(data.len() as u64)?;
         self.inner.write_all(data).map_err(Error::from)?;
         Ok(())
     }
 
+    /// Writes a length-delimited message as a single vectored write: the
+    /// length prefix and `data` are submitted together as `[IoSlice; 2]`,
+    /// so a large payload reaches the underlying writer without first
+    /// being copied through `BufWriter`'s internal buffer.
+    ///
+    /// Flushes any already-buffered bytes first to preserve ordering, then
+    /// writes directly to the underlying writer — bypassing the buffer for
+    /// this call, the same way `write_message` does for oversized `data`.
+    pub fn write_message_vectored(&mut self, data: &[u8]) -> Result<()> {
+        self.inner.flush().map_err(Error::from)?;
+
+        let mut prefix = [0u8; MAX_VARINT_LEN];
+        let prefix_len = encode_varint(&mut prefix, data.len() as u64);
+        let mut slices = [IoSlice::new(&prefix[..prefix_len]), IoSlice::new(data)];
+        write_all_vectored(self.inner.get_mut(), &mut slices)
+    }
+
+    /// Writes many length-delimited messages as a single vectored write:
+    /// every message's length prefix and payload are submitted together as
+    /// one `IoSlice` list, rather than one `write_message` call (and its
+    /// own underlying writes) per message.
+    pub fn write_messages(&mut self, messages: &[&[u8]]) -> Result<()> {
+        self.inner.flush().map_err(Error::from)?;
+
+        let mut prefixes: Vec<([u8; MAX_VARINT_LEN], usize)> = Vec::with_capacity(messages.len());
+        for message in messages {
+            let mut prefix = [0u8; MAX_VARINT_LEN];
+            let prefix_len = encode_varint(&mut prefix, message.len() as u64);
+            prefixes.push((prefix, prefix_len));
+        }
+
+        let mut slices = Vec::with_capacity(messages.len() * 2);
+        for (message, (prefix, prefix_len)) in messages.iter().zip(prefixes.iter()) {
+            slices.push(IoSlice::new(&prefix[..*prefix_len]));
+            slices.push(IoSlice::new(message));
+        }
+
+        write_all_vectored(self.inner.get_mut(), &mut slices)
+    }
+
     /// Flushes the underlying buffer.
     pub fn flush(&mut self) -> Result<()> {
         self.inner.flush().map_err(Error::from)
@@ -113,19 +263,10 @@ impl<W: Write> StreamWriter<W> {
     }
 
     /// Writes a varint to the stream.
-    fn write_varint(&mut self, mut value: u64) -> Result<()> {
-        let mut buf = [0u8; 10];
-        let mut i = 0;
-
-        while value > 0x7f {
-            buf[i] = (value as u8 & 0x7f) | 0x80;
-            value >>= 7;
-            i += 1;
-        }
-        buf[i] = value as u8;
-        i += 1;
-
-        self.inner.write_all(&buf[..i]).map_err(Error::from)
+    fn write_varint(&mut self, value: u64) -> Result<()> {
+        let mut buf = [0u8; MAX_VARINT_LEN];
+        let len = encode_varint(&mut buf, value);
+        self.inner.write_all(&buf[..len]).map_err(Error::from)
     }
 }
 
@@ -136,6 +277,24 @@ impl<W: Write> StreamWriter<W> {
 pub struct StreamReader<R: Read> {
     inner: BufReader<R>,
     max_message_size: usize,
+    max_alloc_size: usize,
+    /// Bytes `read_message_borrowed` has handed out a borrowed slice of but
+    /// not yet `consume`d from `inner`. Applied at the start of the next
+    /// `read_message_borrowed` call rather than immediately, since
+    /// consuming right away would need a mutable borrow of `inner` while
+    /// the slice borrowed from it is still held by the caller.
+    pending_consume: usize,
+    /// Owned fallback buffer for `read_message_borrowed`, reused across
+    /// calls, for frames that straddle the `BufReader`'s fill boundary.
+    borrowed_scratch: Vec<u8>,
+    /// Highest version `read_header` will accept; set via `set_max_version`.
+    max_version: u32,
+    /// When true, every message-reading method errors out until
+    /// `read_header` has been called successfully. Set via
+    /// `StreamReader::with_required_header`.
+    header_required: bool,
+    /// Whether `read_header` has been called successfully yet.
+    header_read: bool,
 }
 
 impl<R: Read> StreamReader<R> {
@@ -144,11 +303,26 @@ impl<R: Read> StreamReader<R> {
         Self::with_capacity(DEFAULT_BUFFER_CAPACITY, reader)
     }
 
+    /// Creates a new StreamReader that requires a valid `StreamHeader` (see
+    /// `read_header`) to be read before any message-reading method will
+    /// succeed, instead of leaving that up to the caller's discipline.
+    pub fn with_required_header(reader: R) -> Self {
+        let mut this = Self::new(reader);
+        this.header_required = true;
+        this
+    }
+
     /// Creates a new StreamReader with the specified buffer capacity.
     pub fn with_capacity(capacity: usize, reader: R) -> Self {
         Self {
             inner: BufReader::with_capacity(capacity, reader),
             max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+            max_alloc_size: DEFAULT_MAX_ALLOC_SIZE,
+            pending_consume: 0,
+            borrowed_scratch: Vec::new(),
+            max_version: DEFAULT_MAX_VERSION,
+            header_required: false,
+            header_read: false,
         }
     }
 
@@ -157,12 +331,73 @@ impl<R: Read> StreamReader<R> {
         self.max_message_size = size;
     }
 
+    /// Sets the maximum number of bytes a single length-delimited field
+    /// read (`read_string`, `read_length_prefixed_bytes`, `sub_reader`) is
+    /// allowed to allocate. Guards against a crafted length prefix forcing
+    /// a multi-gigabyte allocation before the stream has actually produced
+    /// that much data.
+    pub fn set_max_alloc_size(&mut self, size: usize) {
+        self.max_alloc_size = size;
+    }
+
+    /// Sets the highest stream version `read_header` will accept; versions
+    /// above this are rejected with a descriptive error rather than parsed,
+    /// since this crate doesn't know what a newer version's framing means.
+    pub fn set_max_version(&mut self, max_version: u32) {
+        self.max_version = max_version;
+    }
+
+    /// Reads and validates the self-describing stream header written by
+    /// `StreamWriter::write_header`: the fixed `STREAM_MAGIC` sequence,
+    /// followed by a varint version and a varint flags word.
+    ///
+    /// Returns `Error::custom` if the magic doesn't match (this isn't a
+    /// cramberry stream) or the version exceeds `max_version` (this stream
+    /// is newer than what this reader understands), rather than letting
+    /// either silently corrupt the first length varint `read_message` would
+    /// otherwise parse.
+    pub fn read_header(&mut self) -> Result<StreamHeader> {
+        let mut magic = [0u8; 4];
+        self.inner.read_exact(&mut magic).map_err(Error::from)?;
+        if magic != STREAM_MAGIC {
+            return Err(Error::custom(format!(
+                "not a cramberry stream: expected magic {:?}, found {:?}",
+                STREAM_MAGIC, magic
+            )));
+        }
+
+        let version = self.read_length_varint()? as u32;
+        if version > self.max_version {
+            return Err(Error::custom(format!(
+                "stream version {} exceeds maximum supported version {}",
+                version, self.max_version
+            )));
+        }
+        let flags = self.read_length_varint()? as u32;
+
+        self.header_read = true;
+        Ok(StreamHeader { version, flags })
+    }
+
+    /// Returns an error if this reader requires a header (see
+    /// `with_required_header`) that hasn't been read yet. Called at the
+    /// start of every message-reading method.
+    fn check_header_read(&self) -> Result<()> {
+        if self.header_required && !self.header_read {
+            return Err(Error::custom(
+                "stream header required but read_header was not called",
+            ));
+        }
+        Ok(())
+    }
+
     /// Reads a length-delimited message.
     ///
     /// Returns the message data as a Vec<u8>.
     /// Returns an error if the stream ends before a complete message is read.
     pub fn read_message(&mut self) -> Result<Vec<u8>> {
-        let length = self.read_varint()? as usize;
+        self.check_header_read()?;
+        let length = self.read_length_varint()? as usize;
 
         // Check against max message size
         if length > self.max_message_size {
@@ -172,16 +407,15 @@ impl<R: Read> StreamReader<R> {
             )));
         }
 
-        let mut data = vec![0u8; length];
-        self.inner.read_exact(&mut data).map_err(Error::from)?;
-        Ok(data)
+        self.read_incremental(length)
     }
 
     /// Attempts to read a message, returning None if the stream is at EOF.
     ///
     /// This is useful for iterating over all messages in a stream.
     pub fn try_read_message(&mut self) -> Result<Option<Vec<u8>>> {
-        match self.try_read_varint()? {
+        self.check_header_read()?;
+        match self.try_read_length_varint()? {
             Some(length) => {
                 let length = length as usize;
                 if length > self.max_message_size {
@@ -191,14 +425,140 @@ impl<R: Read> StreamReader<R> {
                     )));
                 }
 
-                let mut data = vec![0u8; length];
-                self.inner.read_exact(&mut data).map_err(Error::from)?;
-                Ok(Some(data))
+                Ok(Some(self.read_incremental(length)?))
             }
             None => Ok(None),
         }
     }
 
+    /// Like `read_message`, but clears and reuses a caller-owned buffer
+    /// instead of allocating a fresh `Vec` per call. Returns `Ok(true)`
+    /// with `buf` holding the frame, or `Ok(false)` at EOF (with `buf`
+    /// cleared). Suited to tight decode loops via `buffered_messages`.
+    pub fn read_message_into(&mut self, buf: &mut Vec<u8>) -> Result<bool> {
+        self.check_header_read()?;
+        buf.clear();
+        match self.try_read_length_varint()? {
+            Some(length) => {
+                let length = length as usize;
+                if length > self.max_message_size {
+                    return Err(Error::custom(format!(
+                        "message size {} exceeds maximum {}",
+                        length, self.max_message_size
+                    )));
+                }
+                self.fill_incremental(buf, length)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Zero-copy fast path for `read_message`: if the next frame already
+    /// lies entirely within the `BufReader`'s filled buffer, returns a
+    /// slice directly into it with no copy. Falls back to an owned,
+    /// internally-buffered copy (reused across calls, like
+    /// `read_message_into`) when the frame straddles the fill boundary.
+    ///
+    /// The returned slice's lifetime ties up `self`, so it must be dropped
+    /// (e.g. by going out of scope) before the next call — which is also
+    /// when the bytes it borrowed are actually marked consumed, since doing
+    /// that up front would require mutably borrowing `self.inner` while the
+    /// slice borrowed from it is still live.
+    pub fn read_message_borrowed(&mut self) -> Result<Option<&[u8]>> {
+        self.check_header_read()?;
+        if self.pending_consume > 0 {
+            self.inner.consume(self.pending_consume);
+            self.pending_consume = 0;
+        }
+
+        let length = match self.try_read_length_varint()? {
+            Some(length) => length as usize,
+            None => return Ok(None),
+        };
+        if length > self.max_message_size {
+            return Err(Error::custom(format!(
+                "message size {} exceeds maximum {}",
+                length, self.max_message_size
+            )));
+        }
+
+        let buffered = self.inner.buffer().len();
+        if buffered >= length {
+            self.pending_consume = length;
+            return Ok(Some(&self.inner.buffer()[..length]));
+        }
+
+        // The frame straddles the buffer boundary: take what's already
+        // buffered, then fall back to incremental reads for the rest. Seed
+        // the same bounded initial capacity `read_incremental` does rather
+        // than `length` itself, so a claimed length can't force a large
+        // up-front allocation before the rest of the frame has arrived.
+        let mut data = Vec::with_capacity(length.min(INCREMENTAL_READ_INITIAL_CHUNK));
+        data.extend_from_slice(self.inner.buffer());
+        let already_buffered = data.len();
+        self.inner.consume(already_buffered);
+        self.fill_incremental(&mut data, length - already_buffered)?;
+
+        self.borrowed_scratch = data;
+        Ok(Some(&self.borrowed_scratch))
+    }
+
+    /// Returns an iterator-like helper that reuses one internal buffer
+    /// across messages instead of allocating a `Vec` per element; see
+    /// `BufferedMessageIter::next`.
+    pub fn buffered_messages(&mut self) -> BufferedMessageIter<'_, R> {
+        BufferedMessageIter {
+            reader: self,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Reads exactly `length` bytes, growing the returned buffer's capacity
+    /// geometrically in bounded chunks as bytes actually arrive rather than
+    /// allocating `length` bytes up front.
+    ///
+    /// A claimed `length` only costs a `max_message_size` ceiling, not a
+    /// real allocation: a peer that sends a large length then stalls or
+    /// closes the connection forces at most `INCREMENTAL_READ_INITIAL_CHUNK`
+    /// bytes to be committed before the first short read or EOF is
+    /// observed, rather than the full claimed size. Mirrors protobuf's
+    /// `CodedInputStream` `READ_RAW_BYTES_MAX_ALLOC` growth strategy.
+    fn read_incremental(&mut self, length: usize) -> Result<Vec<u8>> {
+        let mut data = Vec::with_capacity(length.min(INCREMENTAL_READ_INITIAL_CHUNK));
+        self.fill_incremental(&mut data, length)?;
+        Ok(data)
+    }
+
+    /// Appends exactly `additional` more bytes read from the stream onto
+    /// `buf`, growing `buf`'s capacity geometrically in bounded chunks as
+    /// bytes actually arrive. Shared core of `read_incremental` and
+    /// `read_message_into`/`read_message_borrowed`'s buffer-reuse paths.
+    fn fill_incremental(&mut self, buf: &mut Vec<u8>, additional: usize) -> Result<()> {
+        let mut remaining = additional;
+        let mut next_chunk = additional.clamp(1, INCREMENTAL_READ_INITIAL_CHUNK);
+
+        while remaining > 0 {
+            let want = next_chunk.min(remaining);
+            let start = buf.len();
+            buf.resize(start + want, 0);
+
+            let mut filled = 0;
+            while filled < want {
+                let n = self.inner.read(&mut buf[start + filled..start + want])?;
+                if n == 0 {
+                    return Err(Error::UnexpectedEof);
+                }
+                filled += n;
+            }
+
+            remaining -= want;
+            next_chunk = next_chunk.saturating_mul(2);
+        }
+
+        Ok(())
+    }
+
     /// Returns a reference to the underlying reader.
     pub fn get_ref(&self) -> &R {
         self.inner.get_ref()
@@ -209,8 +569,8 @@ impl<R: Read> StreamReader<R> {
         self.inner.get_mut()
     }
 
-    /// Reads a varint from the stream.
-    fn read_varint(&mut self) -> Result<u64> {
+    /// Reads the length prefix of a whole framed message from the stream.
+    fn read_length_varint(&mut self) -> Result<u64> {
         let mut result: u64 = 0;
         let mut shift = 0;
         let mut buf = [0u8; 1];
@@ -228,8 +588,8 @@ impl<R: Read> StreamReader<R> {
         Err(Error::VarintOverflow)
     }
 
-    /// Attempts to read a varint, returning None if at EOF.
-    fn try_read_varint(&mut self) -> Result<Option<u64>> {
+    /// Attempts to read a whole-message length prefix, returning None if at EOF.
+    fn try_read_length_varint(&mut self) -> Result<Option<u64>> {
         let mut result: u64 = 0;
         let mut shift = 0;
         let mut buf = [0u8; 1];
@@ -252,6 +612,206 @@ impl<R: Read> StreamReader<R> {
 
         Err(Error::VarintOverflow)
     }
+
+    /// Reads a single raw byte directly off the stream.
+    pub fn read_byte(&mut self) -> Result<u8> {
+        let mut buf = [0u8; 1];
+        self.inner.read_exact(&mut buf).map_err(Error::from)?;
+        Ok(buf[0])
+    }
+
+    /// Reads `length` raw bytes directly off the stream.
+    pub fn read_bytes(&mut self, length: usize) -> Result<Vec<u8>> {
+        let mut data = vec![0u8; length];
+        self.inner.read_exact(&mut data).map_err(Error::from)?;
+        Ok(data)
+    }
+
+    /// Reads an unsigned varint field value (LEB128) directly off the stream.
+    pub fn read_varint(&mut self) -> Result<u32> {
+        let mut result: u32 = 0;
+        let mut shift = 0;
+
+        for i in 0..10 {
+            let b = self.read_byte()?;
+            if i == 4 && (b & 0xf0) != 0 {
+                return Err(Error::VarintOverflow);
+            }
+            result |= ((b & 0x7f) as u32) << shift;
+            if b & 0x80 == 0 {
+                return Ok(result);
+            }
+            shift += 7;
+        }
+
+        Err(Error::VarintOverflow)
+    }
+
+    /// Reads an unsigned 64-bit varint field value (LEB128) directly off the stream.
+    pub fn read_varint64(&mut self) -> Result<u64> {
+        let mut result: u64 = 0;
+        let mut shift = 0;
+
+        for i in 0..10 {
+            let b = self.read_byte()?;
+            if i == 9 && (b >= 0x80 || b > 1) {
+                return Err(Error::VarintOverflow);
+            }
+            result |= ((b & 0x7f) as u64) << shift;
+            if b & 0x80 == 0 {
+                return Ok(result);
+            }
+            shift += 7;
+        }
+
+        Err(Error::VarintOverflow)
+    }
+
+    /// Reads a signed varint using ZigZag decoding.
+    pub fn read_svarint(&mut self) -> Result<i32> {
+        Ok(zigzag_decode_32(self.read_varint()?))
+    }
+
+    /// Reads a signed 64-bit varint using ZigZag decoding.
+    pub fn read_svarint64(&mut self) -> Result<i64> {
+        Ok(zigzag_decode_64(self.read_varint64()?))
+    }
+
+    /// Reads a boolean directly off the stream.
+    pub fn read_bool(&mut self) -> Result<bool> {
+        Ok(self.read_byte()? != 0)
+    }
+
+    /// Reads a 32-bit signed integer directly off the stream.
+    pub fn read_int32(&mut self) -> Result<i32> {
+        self.read_svarint()
+    }
+
+    /// Reads a 64-bit signed integer directly off the stream.
+    pub fn read_int64(&mut self) -> Result<i64> {
+        self.read_svarint64()
+    }
+
+    /// Reads a 32-bit unsigned integer directly off the stream.
+    pub fn read_uint32(&mut self) -> Result<u32> {
+        self.read_varint()
+    }
+
+    /// Reads a 64-bit unsigned integer directly off the stream.
+    pub fn read_uint64(&mut self) -> Result<u64> {
+        self.read_varint64()
+    }
+
+    /// Reads a SCALE-style compact variable-width integer (`WireType::Compact`)
+    /// directly off the stream. See `compact_decode` for the format.
+    pub fn read_compact(&mut self) -> Result<u64> {
+        let first = self.read_byte()?;
+        match first & 0b11 {
+            0b00 => Ok((first >> 2) as u64),
+            0b01 => {
+                let b1 = self.read_byte()?;
+                Ok((u16::from_le_bytes([first, b1]) >> 2) as u64)
+            }
+            0b10 => {
+                let rest = self.read_bytes(3)?;
+                Ok((u32::from_le_bytes([first, rest[0], rest[1], rest[2]]) >> 2) as u64)
+            }
+            0b11 => {
+                let following = ((first >> 2) as usize) + 4;
+                if following > 8 {
+                    return Err(Error::VarintOverflow);
+                }
+                let rest = self.read_bytes(following)?;
+                let mut buf = [0u8; 8];
+                buf[..following].copy_from_slice(&rest);
+                Ok(u64::from_le_bytes(buf))
+            }
+            _ => unreachable!("2-bit mask can only be 0..=3"),
+        }
+    }
+
+    /// Reads a V2 compact field tag directly off the stream.
+    pub fn read_tag(&mut self) -> Result<FieldTag> {
+        let first = self.read_byte()?;
+        if first == END_MARKER {
+            return Ok(FieldTag::new(0, WireType::Varint));
+        }
+
+        let wire_type_val = (first & TAG_WIRE_TYPE_MASK) >> TAG_WIRE_TYPE_SHIFT;
+        let wire_type = WireType::from_u8(wire_type_val).ok_or(Error::InvalidWireType(first))?;
+
+        if (first & TAG_EXTENDED_BIT) == 0 {
+            let field_number = (first >> TAG_FIELD_NUM_SHIFT) as u32;
+            Ok(FieldTag::new(field_number, wire_type))
+        } else {
+            let field_number = self.read_varint()?;
+            Ok(FieldTag::new(field_number, wire_type))
+        }
+    }
+
+    /// Checks a prospective length against `max_alloc_size` before
+    /// allocating, so a crafted length prefix can't force a huge
+    /// allocation before the stream has actually produced that much data.
+    fn check_alloc_size(&self, length: usize) -> Result<()> {
+        if length > self.max_alloc_size {
+            return Err(Error::LengthLimitExceeded {
+                requested: length,
+                limit: self.max_alloc_size,
+            });
+        }
+        Ok(())
+    }
+
+    /// Reads a length-prefixed UTF-8 string directly off the stream.
+    pub fn read_string(&mut self) -> Result<String> {
+        let length = self.read_varint()? as usize;
+        self.check_alloc_size(length)?;
+        let bytes = self.read_bytes(length)?;
+        String::from_utf8(bytes).map_err(|_| Error::InvalidUtf8)
+    }
+
+    /// Reads length-prefixed bytes directly off the stream.
+    pub fn read_length_prefixed_bytes(&mut self) -> Result<Vec<u8>> {
+        let length = self.read_varint()? as usize;
+        self.check_alloc_size(length)?;
+        self.read_bytes(length)
+    }
+
+    /// Skips a field, given the tag that was just read for it.
+    pub fn skip_field(&mut self, tag: FieldTag) -> Result<()> {
+        match tag.wire_type {
+            WireType::Varint | WireType::SVarint => {
+                self.read_varint64()?;
+            }
+            WireType::Fixed64 => {
+                self.read_bytes(8)?;
+            }
+            WireType::Bytes => {
+                let length = self.read_varint()? as usize;
+                self.check_alloc_size(length)?;
+                self.read_bytes(length)?;
+            }
+            WireType::Fixed32 => {
+                self.read_bytes(4)?;
+            }
+            WireType::Compact => {
+                self.read_compact()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads a length-prefixed nested message into an owned, `max_alloc_size`-
+    /// bounded buffer, mirroring `Reader::sub_reader` for stream sources.
+    ///
+    /// Unlike `Reader::sub_reader`, this can't hand back a borrowing
+    /// `Reader` directly (there is no persistent buffer to borrow from), so
+    /// the caller decodes from the returned bytes themselves:
+    /// `let bytes = stream.sub_reader(len)?; let mut sub = Reader::new(&bytes);`
+    pub fn sub_reader(&mut self, length: usize) -> Result<Vec<u8>> {
+        self.check_alloc_size(length)?;
+        self.read_bytes(length)
+    }
 }
 
 /// Iterator over messages in a stream.
@@ -278,71 +838,477 @@ impl<R: Read> Iterator for MessageIter<'_, R> {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::io::Cursor;
-
-    #[test]
-    fn test_stream_roundtrip() {
-        let mut buffer = Vec::new();
+/// Reuses one buffer across messages rather than allocating a `Vec` per
+/// element, unlike `MessageIter`. Each item borrows that shared buffer, so
+/// this can't implement `std::iter::Iterator` (an `Item` can't borrow from
+/// the iterator itself); drive it with
+/// `while let Some(frame) = iter.next_message()?` instead of a `for` loop.
+pub struct BufferedMessageIter<'a, R: Read> {
+    reader: &'a mut StreamReader<R>,
+    buf: Vec<u8>,
+}
 
-        // Write messages
-        {
-            let mut stream = StreamWriter::new(&mut buffer);
-            stream.write_message(b"hello").unwrap();
-            stream.write_message(b"world").unwrap();
-            stream.write_message(b"!").unwrap();
-            stream.flush().unwrap();
+impl<R: Read> BufferedMessageIter<'_, R> {
+    /// Reads the next message into the shared buffer, returning `None` at
+    /// EOF.
+    pub fn next_message(&mut self) -> Result<Option<&[u8]>> {
+        if self.reader.read_message_into(&mut self.buf)? {
+            Ok(Some(&self.buf))
+        } else {
+            Ok(None)
         }
+    }
+}
 
-        // Read messages back
-        {
-            let cursor = Cursor::new(&buffer);
-            let mut stream = StreamReader::new(cursor);
+/// A message index built by `SeekableStreamReader::build_index`: one
+/// `(offset, length)` pair per message, where `offset` is the absolute
+/// byte position of the message's length-prefix varint in the underlying
+/// stream, and `length` is the decoded message body length.
+pub type MessageIndex = Vec<(u64, u64)>;
 
-            assert_eq!(stream.read_message().unwrap(), b"hello");
-            assert_eq!(stream.read_message().unwrap(), b"world");
-            assert_eq!(stream.read_message().unwrap(), b"!");
+/// A `StreamReader` over a `Read + Seek` source that can jump directly to
+/// the Nth message instead of scanning forward from the start, once an
+/// index of message offsets has been built (or supplied up front via
+/// `with_index`).
+pub struct SeekableStreamReader<R: Read + Seek> {
+    stream: StreamReader<R>,
+    index: MessageIndex,
+}
+
+impl<R: Read + Seek> SeekableStreamReader<R> {
+    /// Creates a new SeekableStreamReader wrapping the given reader, with
+    /// an empty index — call `build_index` before `seek_to_message`.
+    pub fn new(reader: R) -> Self {
+        Self {
+            stream: StreamReader::new(reader),
+            index: Vec::new(),
         }
     }
 
-    #[test]
-    fn test_stream_empty_message() {
-        let mut buffer = Vec::new();
-
-        {
-            let mut stream = StreamWriter::new(&mut buffer);
-            stream.write_message(b"").unwrap();
-            stream.flush().unwrap();
+    /// Creates a new SeekableStreamReader with the specified buffer
+    /// capacity.
+    pub fn with_capacity(capacity: usize, reader: R) -> Self {
+        Self {
+            stream: StreamReader::with_capacity(capacity, reader),
+            index: Vec::new(),
         }
+    }
 
-        {
-            let cursor = Cursor::new(&buffer);
-            let mut stream = StreamReader::new(cursor);
-            assert_eq!(stream.read_message().unwrap(), b"");
+    /// Creates a new SeekableStreamReader from a previously persisted
+    /// index (see `into_index`), skipping the initial full-stream scan
+    /// `build_index` would otherwise need to perform.
+    pub fn with_index(reader: R, index: MessageIndex) -> Self {
+        Self {
+            stream: StreamReader::new(reader),
+            index,
         }
     }
 
-    #[test]
-    fn test_stream_large_message() {
-        let data = vec![0xABu8; 1000];
-        let mut buffer = Vec::new();
+    /// Walks the whole stream once from the beginning, recording each
+    /// message's `(offset, length)` into the index. After this,
+    /// `seek_to_message` is O(1) and `message_count` reflects the stream.
+    pub fn build_index(&mut self) -> Result<()> {
+        self.index.clear();
+        self.stream.inner.rewind().map_err(Error::from)?;
 
-        {
-            let mut stream = StreamWriter::new(&mut buffer);
-            stream.write_message(&data).unwrap();
-            stream.flush().unwrap();
+        loop {
+            let offset = self.current_offset()?;
+            match self.stream.try_read_message()? {
+                Some(data) => self.index.push((offset, data.len() as u64)),
+                None => break,
+            }
         }
 
-        {
-            let cursor = Cursor::new(&buffer);
-            let mut stream = StreamReader::new(cursor);
-            assert_eq!(stream.read_message().unwrap(), data);
-        }
+        Ok(())
     }
 
-    #[test]
+    /// The number of messages in the index. Zero until `build_index` (or
+    /// `with_index`) has populated it.
+    pub fn message_count(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Seeks directly to the `n`th message (0-indexed) and reads it,
+    /// without scanning the messages before it. Requires the index to
+    /// already cover `n`, via `build_index` or `with_index`.
+    pub fn seek_to_message(&mut self, n: usize) -> Result<Vec<u8>> {
+        let &(offset, _length) = self.index.get(n).ok_or_else(|| {
+            Error::custom(format!(
+                "message index {} out of range: index has {} entries",
+                n,
+                self.index.len()
+            ))
+        })?;
+        self.stream
+            .inner
+            .seek(SeekFrom::Start(offset))
+            .map_err(Error::from)?;
+        self.stream.read_message()
+    }
+
+    /// Consumes this SeekableStreamReader, returning its index so it can be
+    /// persisted and later passed back to `with_index` to skip rebuilding
+    /// it with another full scan.
+    pub fn into_index(self) -> MessageIndex {
+        self.index
+    }
+
+    /// Returns a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        self.stream.get_ref()
+    }
+
+    /// Returns a mutable reference to the underlying reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        self.stream.get_mut()
+    }
+
+    /// The current logical offset into the underlying stream, accounting
+    /// for bytes the internal `BufReader` has buffered but not yet
+    /// consumed. `BufReader`'s `stream_position` subtracts the buffered,
+    /// unconsumed bytes from the underlying reader's position rather than
+    /// discarding the buffer to find out, unlike an arbitrary `seek` call.
+    fn current_offset(&mut self) -> Result<u64> {
+        self.stream.inner.stream_position().map_err(Error::from)
+    }
+}
+
+/// Writes individual fields directly to an `io::Write`, buffering through a
+/// `BufWriter` so callers don't hold a whole message's `Vec<u8>` in memory
+/// the way `Writer` does. Named after protobuf's `CodedOutputStream`, which
+/// this mirrors: tags and values are written one at a time and the internal
+/// buffer flushes itself whenever a write would overflow it.
+pub struct CodedOutputStream<W: Write> {
+    inner: BufWriter<W>,
+}
+
+impl<W: Write> CodedOutputStream<W> {
+    /// Creates a new `CodedOutputStream` wrapping `writer` with the default
+    /// buffer capacity.
+    pub fn new(writer: W) -> Self {
+        Self::with_capacity(DEFAULT_BUFFER_CAPACITY, writer)
+    }
+
+    /// Creates a new `CodedOutputStream` with the specified buffer capacity.
+    pub fn with_capacity(capacity: usize, writer: W) -> Self {
+        Self {
+            inner: BufWriter::with_capacity(capacity, writer),
+        }
+    }
+
+    /// Writes a V2 compact field tag.
+    pub fn write_tag(&mut self, field_number: u32, wire_type: WireType) -> Result<()> {
+        let tag = FieldTag::new(field_number, wire_type);
+        let mut buf = [0u8; MAX_COMPACT_TAG_LEN];
+        let len = tag.encode_compact_into(&mut buf);
+        self.inner.write_all(&buf[..len]).map_err(Error::from)
+    }
+
+    /// Writes an unsigned varint (LEB128).
+    pub fn write_varint(&mut self, mut value: u64) -> Result<()> {
+        let mut buf = [0u8; MAX_VARINT_LEN];
+        let mut len = 0;
+        while value > 0x7f {
+            buf[len] = (value as u8 & 0x7f) | 0x80;
+            value >>= 7;
+            len += 1;
+        }
+        buf[len] = value as u8;
+        len += 1;
+        self.inner.write_all(&buf[..len]).map_err(Error::from)
+    }
+
+    /// Writes a signed varint using ZigZag encoding.
+    pub fn write_svarint(&mut self, value: i64) -> Result<()> {
+        self.write_varint(zigzag_encode_64(value))
+    }
+
+    /// Writes a fixed 32-bit value (little-endian).
+    pub fn write_fixed32(&mut self, value: u32) -> Result<()> {
+        self.inner.write_all(&value.to_le_bytes()).map_err(Error::from)
+    }
+
+    /// Writes a fixed 64-bit value (little-endian).
+    pub fn write_fixed64(&mut self, value: u64) -> Result<()> {
+        self.inner.write_all(&value.to_le_bytes()).map_err(Error::from)
+    }
+
+    /// Writes raw bytes with no length prefix.
+    pub fn write_bytes(&mut self, data: &[u8]) -> Result<()> {
+        self.inner.write_all(data).map_err(Error::from)
+    }
+
+    /// Flushes the underlying buffer.
+    pub fn flush(&mut self) -> Result<()> {
+        self.inner.flush().map_err(Error::from)
+    }
+
+    /// Returns a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        self.inner.get_ref()
+    }
+
+    /// Returns a mutable reference to the underlying writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        self.inner.get_mut()
+    }
+
+    /// Consumes this `CodedOutputStream`, returning the underlying writer.
+    ///
+    /// This will flush any buffered data before returning the inner writer.
+    /// Returns an error if flushing fails.
+    pub fn into_inner(self) -> Result<W> {
+        self.inner.into_inner().map_err(|e| Error::from(e.into_error()))
+    }
+}
+
+/// Reads individual fields directly from an `io::Read`, buffering through a
+/// `BufReader` so callers can decode a stream incrementally rather than
+/// holding a whole message in memory. Named after protobuf's
+/// `CodedInputStream`, which this mirrors.
+///
+/// `read_tag` decodes straight out of the `BufReader`'s own buffer with
+/// `decode_compact_tag` — the same fast-path decoder `Reader::read_tag`
+/// uses — whenever the next tag is already fully buffered, and only falls
+/// back to a byte-at-a-time read when the tag straddles a buffer refill.
+pub struct CodedInputStream<R: Read> {
+    inner: BufReader<R>,
+}
+
+impl<R: Read> CodedInputStream<R> {
+    /// Creates a new `CodedInputStream` wrapping `reader` with the default
+    /// buffer capacity.
+    pub fn new(reader: R) -> Self {
+        Self::with_capacity(DEFAULT_BUFFER_CAPACITY, reader)
+    }
+
+    /// Creates a new `CodedInputStream` with the specified buffer capacity.
+    pub fn with_capacity(capacity: usize, reader: R) -> Self {
+        Self {
+            inner: BufReader::with_capacity(capacity, reader),
+        }
+    }
+
+    /// Reads a single raw byte directly off the stream.
+    fn read_byte(&mut self) -> Result<u8> {
+        let mut buf = [0u8; 1];
+        self.inner.read_exact(&mut buf).map_err(Error::from)?;
+        Ok(buf[0])
+    }
+
+    /// Reads `length` raw bytes directly off the stream.
+    pub fn read_bytes(&mut self, length: usize) -> Result<Vec<u8>> {
+        let mut data = vec![0u8; length];
+        self.inner.read_exact(&mut data).map_err(Error::from)?;
+        Ok(data)
+    }
+
+    /// Reads a V2 compact field tag.
+    ///
+    /// Tries `decode_compact_tag` directly against whatever the `BufReader`
+    /// already has buffered, which avoids a per-byte read for the common
+    /// case of a tag that doesn't straddle a buffer refill. Falls back to
+    /// `read_tag_slow` otherwise (an empty buffer, or a tag whose bytes run
+    /// past what's currently buffered).
+    pub fn read_tag(&mut self) -> Result<FieldTag> {
+        let buffered = self.inner.fill_buf().map_err(Error::from)?;
+        if let Some(result) = decode_compact_tag(buffered) {
+            self.inner.consume(result.bytes_read);
+            return Ok(FieldTag::new(result.field_number, result.wire_type));
+        }
+        self.read_tag_slow()
+    }
+
+    /// Byte-at-a-time fallback for `read_tag`.
+    fn read_tag_slow(&mut self) -> Result<FieldTag> {
+        let first = self.read_byte()?;
+        if first == END_MARKER {
+            return Ok(FieldTag::new(0, WireType::Varint));
+        }
+
+        let wire_type_val = (first & TAG_WIRE_TYPE_MASK) >> TAG_WIRE_TYPE_SHIFT;
+        let wire_type = WireType::from_u8(wire_type_val).ok_or(Error::InvalidWireType(first))?;
+
+        if (first & TAG_EXTENDED_BIT) == 0 {
+            let field_number = (first >> TAG_FIELD_NUM_SHIFT) as u32;
+            Ok(FieldTag::new(field_number, wire_type))
+        } else {
+            let field_number = self.read_varint()? as u32;
+            Ok(FieldTag::new(field_number, wire_type))
+        }
+    }
+
+    /// Reads an unsigned varint (LEB128) field value directly off the stream.
+    pub fn read_varint(&mut self) -> Result<u64> {
+        let mut result: u64 = 0;
+        let mut shift = 0;
+
+        for i in 0..MAX_VARINT_LEN {
+            let b = self.read_byte()?;
+            if i == 9 && (b >= 0x80 || b > 1) {
+                return Err(Error::VarintOverflow);
+            }
+            result |= ((b & 0x7f) as u64) << shift;
+            if b & 0x80 == 0 {
+                return Ok(result);
+            }
+            shift += 7;
+        }
+
+        Err(Error::VarintOverflow)
+    }
+
+    /// Reads a signed varint using ZigZag decoding.
+    pub fn read_svarint(&mut self) -> Result<i64> {
+        Ok(zigzag_decode_64(self.read_varint()?))
+    }
+
+    /// Reads a fixed 32-bit value (little-endian) directly off the stream.
+    pub fn read_fixed32(&mut self) -> Result<u32> {
+        let bytes = self.read_bytes(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// Reads a fixed 64-bit value (little-endian) directly off the stream.
+    pub fn read_fixed64(&mut self) -> Result<u64> {
+        let bytes = self.read_bytes(8)?;
+        Ok(u64::from_le_bytes([
+            bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7],
+        ]))
+    }
+
+    /// Reads a SCALE-style compact variable-width integer (`WireType::Compact`)
+    /// directly off the stream. See `compact_decode` for the format.
+    pub fn read_compact(&mut self) -> Result<u64> {
+        let first = self.read_byte()?;
+        match first & 0b11 {
+            0b00 => Ok((first >> 2) as u64),
+            0b01 => {
+                let b1 = self.read_byte()?;
+                Ok((u16::from_le_bytes([first, b1]) >> 2) as u64)
+            }
+            0b10 => {
+                let rest = self.read_bytes(3)?;
+                Ok((u32::from_le_bytes([first, rest[0], rest[1], rest[2]]) >> 2) as u64)
+            }
+            0b11 => {
+                let following = ((first >> 2) as usize) + 4;
+                if following > 8 {
+                    return Err(Error::VarintOverflow);
+                }
+                let rest = self.read_bytes(following)?;
+                let mut buf = [0u8; 8];
+                buf[..following].copy_from_slice(&rest);
+                Ok(u64::from_le_bytes(buf))
+            }
+            _ => unreachable!("2-bit mask can only be 0..=3"),
+        }
+    }
+
+    /// Skips a field, given the tag that was just read for it.
+    pub fn skip_field(&mut self, tag: FieldTag) -> Result<()> {
+        match tag.wire_type {
+            WireType::Varint | WireType::SVarint => {
+                self.read_varint()?;
+            }
+            WireType::Fixed64 => {
+                self.read_bytes(8)?;
+            }
+            WireType::Bytes => {
+                let length = self.read_varint()? as usize;
+                self.read_bytes(length)?;
+            }
+            WireType::Fixed32 => {
+                self.read_bytes(4)?;
+            }
+            WireType::Compact => {
+                self.read_compact()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        self.inner.get_ref()
+    }
+
+    /// Returns a mutable reference to the underlying reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        self.inner.get_mut()
+    }
+
+    /// Consumes this `CodedInputStream`, returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner.into_inner()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_stream_roundtrip() {
+        let mut buffer = Vec::new();
+
+        // Write messages
+        {
+            let mut stream = StreamWriter::new(&mut buffer);
+            stream.write_message(b"hello").unwrap();
+            stream.write_message(b"world").unwrap();
+            stream.write_message(b"!").unwrap();
+            stream.flush().unwrap();
+        }
+
+        // Read messages back
+        {
+            let cursor = Cursor::new(&buffer);
+            let mut stream = StreamReader::new(cursor);
+
+            assert_eq!(stream.read_message().unwrap(), b"hello");
+            assert_eq!(stream.read_message().unwrap(), b"world");
+            assert_eq!(stream.read_message().unwrap(), b"!");
+        }
+    }
+
+    #[test]
+    fn test_stream_empty_message() {
+        let mut buffer = Vec::new();
+
+        {
+            let mut stream = StreamWriter::new(&mut buffer);
+            stream.write_message(b"").unwrap();
+            stream.flush().unwrap();
+        }
+
+        {
+            let cursor = Cursor::new(&buffer);
+            let mut stream = StreamReader::new(cursor);
+            assert_eq!(stream.read_message().unwrap(), b"");
+        }
+    }
+
+    #[test]
+    fn test_stream_large_message() {
+        let data = vec![0xABu8; 1000];
+        let mut buffer = Vec::new();
+
+        {
+            let mut stream = StreamWriter::new(&mut buffer);
+            stream.write_message(&data).unwrap();
+            stream.flush().unwrap();
+        }
+
+        {
+            let cursor = Cursor::new(&buffer);
+            let mut stream = StreamReader::new(cursor);
+            assert_eq!(stream.read_message().unwrap(), data);
+        }
+    }
+
+    #[test]
     fn test_stream_iterator() {
         let mut buffer = Vec::new();
 
@@ -391,4 +1357,516 @@ mod tests {
 
         assert!(stream.read_message().is_err());
     }
+
+    #[test]
+    fn test_stream_field_level_decode() {
+        use crate::writer::Writer;
+
+        let mut writer = Writer::new();
+        writer.write_int32_field(1, -42).unwrap();
+        writer.write_string_field(2, "hello").unwrap();
+        writer.write_end_marker().unwrap();
+        let data = writer.into_bytes();
+
+        let cursor = Cursor::new(data);
+        let mut stream = StreamReader::new(cursor);
+
+        let tag1 = stream.read_tag().unwrap();
+        assert_eq!(tag1.field_number, 1);
+        assert_eq!(stream.read_int32().unwrap(), -42);
+
+        let tag2 = stream.read_tag().unwrap();
+        assert_eq!(tag2.field_number, 2);
+        assert_eq!(stream.read_string().unwrap(), "hello");
+
+        let end_tag = stream.read_tag().unwrap();
+        assert_eq!(end_tag.field_number, 0);
+    }
+
+    #[test]
+    fn test_stream_read_tag_and_string() {
+        use crate::writer::Writer;
+
+        let mut writer = Writer::new();
+        writer.write_string_field(3, "streamed").unwrap();
+        let data = writer.into_bytes();
+
+        let cursor = Cursor::new(data);
+        let mut stream = StreamReader::new(cursor);
+
+        let tag = stream.read_tag().unwrap();
+        assert_eq!(tag.field_number, 3);
+        assert_eq!(tag.wire_type, WireType::Bytes);
+        assert_eq!(stream.read_string().unwrap(), "streamed");
+    }
+
+    #[test]
+    fn test_stream_max_alloc_size() {
+        use crate::writer::Writer;
+
+        let mut writer = Writer::new();
+        writer.write_string_field(1, "this string is too long for the cap").unwrap();
+        let data = writer.into_bytes();
+
+        let cursor = Cursor::new(data);
+        let mut stream = StreamReader::new(cursor);
+        stream.set_max_alloc_size(4);
+
+        let _tag = stream.read_tag().unwrap();
+        assert!(matches!(
+            stream.read_string().unwrap_err(),
+            Error::LengthLimitExceeded { .. }
+        ));
+    }
+
+    #[test]
+    fn test_stream_read_message_larger_than_initial_chunk() {
+        let data = vec![0xCDu8; INCREMENTAL_READ_INITIAL_CHUNK * 3 + 17];
+        let mut buffer = Vec::new();
+
+        {
+            let mut stream = StreamWriter::new(&mut buffer);
+            stream.write_message(&data).unwrap();
+            stream.flush().unwrap();
+        }
+
+        let cursor = Cursor::new(&buffer);
+        let mut stream = StreamReader::new(cursor);
+        assert_eq!(stream.read_message().unwrap(), data);
+    }
+
+    #[test]
+    fn test_stream_read_message_truncated_stream_is_unexpected_eof() {
+        let mut buffer = Vec::new();
+        {
+            let mut stream = StreamWriter::new(&mut buffer);
+            stream.write_message(&[0u8; 100]).unwrap();
+            stream.flush().unwrap();
+        }
+        // Cut the stream off partway through the message body.
+        buffer.truncate(buffer.len() - 10);
+
+        let cursor = Cursor::new(buffer);
+        let mut stream = StreamReader::new(cursor);
+        assert!(matches!(
+            stream.read_message().unwrap_err(),
+            Error::UnexpectedEof
+        ));
+    }
+
+    #[test]
+    fn test_read_message_into_reuses_buffer() {
+        let mut buffer = Vec::new();
+        {
+            let mut stream = StreamWriter::new(&mut buffer);
+            stream.write_message(b"one").unwrap();
+            stream.write_message(b"two").unwrap();
+            stream.flush().unwrap();
+        }
+
+        let cursor = Cursor::new(buffer);
+        let mut stream = StreamReader::new(cursor);
+        let mut buf = Vec::new();
+
+        assert!(stream.read_message_into(&mut buf).unwrap());
+        assert_eq!(buf, b"one");
+
+        assert!(stream.read_message_into(&mut buf).unwrap());
+        assert_eq!(buf, b"two");
+
+        assert!(!stream.read_message_into(&mut buf).unwrap());
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_buffered_messages_iterator() {
+        let mut buffer = Vec::new();
+        {
+            let mut stream = StreamWriter::new(&mut buffer);
+            stream.write_message(b"a").unwrap();
+            stream.write_message(b"bb").unwrap();
+            stream.write_message(b"ccc").unwrap();
+            stream.flush().unwrap();
+        }
+
+        let cursor = Cursor::new(buffer);
+        let mut stream = StreamReader::new(cursor);
+        let mut iter = stream.buffered_messages();
+
+        assert_eq!(iter.next_message().unwrap(), Some(&b"a"[..]));
+        assert_eq!(iter.next_message().unwrap(), Some(&b"bb"[..]));
+        assert_eq!(iter.next_message().unwrap(), Some(&b"ccc"[..]));
+        assert_eq!(iter.next_message().unwrap(), None);
+    }
+
+    #[test]
+    fn test_read_message_borrowed_fast_path_zero_copy() {
+        let mut buffer = Vec::new();
+        {
+            let mut stream = StreamWriter::new(&mut buffer);
+            stream.write_message(b"small frame").unwrap();
+            stream.flush().unwrap();
+        }
+
+        let cursor = Cursor::new(buffer);
+        let mut stream = StreamReader::new(cursor);
+        assert_eq!(stream.read_message_borrowed().unwrap(), Some(&b"small frame"[..]));
+        assert_eq!(stream.read_message_borrowed().unwrap(), None);
+    }
+
+    #[test]
+    fn test_read_message_borrowed_multiple_frames() {
+        let mut buffer = Vec::new();
+        {
+            let mut stream = StreamWriter::new(&mut buffer);
+            stream.write_message(b"one").unwrap();
+            stream.write_message(b"two").unwrap();
+            stream.flush().unwrap();
+        }
+
+        let cursor = Cursor::new(buffer);
+        let mut stream = StreamReader::new(cursor);
+        assert_eq!(stream.read_message_borrowed().unwrap(), Some(&b"one"[..]));
+        assert_eq!(stream.read_message_borrowed().unwrap(), Some(&b"two"[..]));
+        assert_eq!(stream.read_message_borrowed().unwrap(), None);
+    }
+
+    #[test]
+    fn test_read_message_borrowed_falls_back_when_frame_straddles_buffer() {
+        // A buffer capacity smaller than the message forces the frame to
+        // straddle the `BufReader`'s fill boundary, exercising the owned
+        // fallback path rather than the zero-copy one.
+        let mut buffer = Vec::new();
+        let data = vec![0x42u8; 64];
+        {
+            let mut stream = StreamWriter::new(&mut buffer);
+            stream.write_message(&data).unwrap();
+            stream.flush().unwrap();
+        }
+
+        let cursor = Cursor::new(buffer);
+        let mut stream = StreamReader::with_capacity(8, cursor);
+        assert_eq!(stream.read_message_borrowed().unwrap(), Some(&data[..]));
+    }
+
+    #[test]
+    fn test_write_message_vectored_round_trip() {
+        let mut buffer = Vec::new();
+        {
+            let mut stream = StreamWriter::new(&mut buffer);
+            stream.write_message_vectored(b"vectored frame").unwrap();
+            stream.flush().unwrap();
+        }
+
+        let cursor = Cursor::new(buffer);
+        let mut stream = StreamReader::new(cursor);
+        assert_eq!(stream.read_message().unwrap(), b"vectored frame");
+    }
+
+    #[test]
+    fn test_write_message_switches_to_vectored_path_above_capacity() {
+        // `data` exceeds the small buffer capacity, so `write_message`
+        // should route through `write_message_vectored` rather than
+        // issuing the length prefix and the payload as separate writes.
+        let data = vec![0x7Au8; 256];
+        let mut buffer = Vec::new();
+        {
+            let mut stream = StreamWriter::with_capacity(16, &mut buffer);
+            stream.write_message(&data).unwrap();
+            stream.flush().unwrap();
+        }
+
+        let cursor = Cursor::new(buffer);
+        let mut stream = StreamReader::new(cursor);
+        assert_eq!(stream.read_message().unwrap(), data);
+    }
+
+    #[test]
+    fn test_write_messages_batch_round_trip() {
+        let mut buffer = Vec::new();
+        {
+            let mut stream = StreamWriter::new(&mut buffer);
+            stream
+                .write_messages(&[b"one".as_slice(), b"two".as_slice(), b"three".as_slice()])
+                .unwrap();
+            stream.flush().unwrap();
+        }
+
+        let cursor = Cursor::new(buffer);
+        let mut stream = StreamReader::new(cursor);
+        assert_eq!(stream.read_message().unwrap(), b"one");
+        assert_eq!(stream.read_message().unwrap(), b"two");
+        assert_eq!(stream.read_message().unwrap(), b"three");
+    }
+
+    #[test]
+    fn test_stream_header_round_trip() {
+        let mut buffer = Vec::new();
+        {
+            let mut stream = StreamWriter::new(&mut buffer);
+            stream.write_header(3).unwrap();
+            stream.write_message(b"payload").unwrap();
+            stream.flush().unwrap();
+        }
+
+        let cursor = Cursor::new(buffer);
+        let mut stream = StreamReader::new(cursor);
+        let header = stream.read_header().unwrap();
+        assert_eq!(header, StreamHeader { version: 3, flags: 0 });
+        assert_eq!(stream.read_message().unwrap(), b"payload");
+    }
+
+    #[test]
+    fn test_stream_header_carries_flags() {
+        let mut buffer = Vec::new();
+        {
+            let mut stream = StreamWriter::new(&mut buffer);
+            stream.write_header_with_flags(1, 0b101).unwrap();
+            stream.flush().unwrap();
+        }
+
+        let cursor = Cursor::new(buffer);
+        let mut stream = StreamReader::new(cursor);
+        let header = stream.read_header().unwrap();
+        assert_eq!(header, StreamHeader { version: 1, flags: 0b101 });
+    }
+
+    #[test]
+    fn test_stream_header_rejects_unknown_magic() {
+        let cursor = Cursor::new(b"nope".to_vec());
+        let mut stream = StreamReader::new(cursor);
+        assert!(matches!(stream.read_header().unwrap_err(), Error::Custom(_)));
+    }
+
+    #[test]
+    fn test_stream_header_rejects_version_above_maximum() {
+        let mut buffer = Vec::new();
+        {
+            let mut stream = StreamWriter::new(&mut buffer);
+            stream.write_header(5).unwrap();
+            stream.flush().unwrap();
+        }
+
+        let cursor = Cursor::new(buffer);
+        let mut stream = StreamReader::new(cursor);
+        stream.set_max_version(4);
+        assert!(matches!(stream.read_header().unwrap_err(), Error::Custom(_)));
+    }
+
+    #[test]
+    fn test_required_header_blocks_reads_until_read_header_called() {
+        let mut buffer = Vec::new();
+        {
+            let mut stream = StreamWriter::new(&mut buffer);
+            stream.write_header(1).unwrap();
+            stream.write_message(b"payload").unwrap();
+            stream.flush().unwrap();
+        }
+
+        let cursor = Cursor::new(buffer);
+        let mut stream = StreamReader::with_required_header(cursor);
+        assert!(matches!(
+            stream.read_message().unwrap_err(),
+            Error::Custom(_)
+        ));
+    }
+
+    #[test]
+    fn test_required_header_allows_reads_after_read_header_called() {
+        let mut buffer = Vec::new();
+        {
+            let mut stream = StreamWriter::new(&mut buffer);
+            stream.write_header(1).unwrap();
+            stream.write_message(b"payload").unwrap();
+            stream.flush().unwrap();
+        }
+
+        let cursor = Cursor::new(buffer);
+        let mut stream = StreamReader::with_required_header(cursor);
+        stream.read_header().unwrap();
+        assert_eq!(stream.read_message().unwrap(), b"payload");
+    }
+
+    #[test]
+    fn test_seekable_stream_build_index_then_seek_to_message() {
+        let mut buffer = Vec::new();
+        {
+            let mut stream = StreamWriter::new(&mut buffer);
+            stream.write_message(b"one").unwrap();
+            stream.write_message(b"two").unwrap();
+            stream.write_message(b"three").unwrap();
+            stream.flush().unwrap();
+        }
+
+        let cursor = Cursor::new(buffer);
+        let mut stream = SeekableStreamReader::new(cursor);
+        stream.build_index().unwrap();
+        assert_eq!(stream.message_count(), 3);
+
+        // Seeking out of order exercises that each call jumps directly to
+        // its message rather than relying on forward-only scanning.
+        assert_eq!(stream.seek_to_message(2).unwrap(), b"three");
+        assert_eq!(stream.seek_to_message(0).unwrap(), b"one");
+        assert_eq!(stream.seek_to_message(1).unwrap(), b"two");
+    }
+
+    #[test]
+    fn test_seekable_stream_seek_to_message_out_of_range() {
+        let mut buffer = Vec::new();
+        {
+            let mut stream = StreamWriter::new(&mut buffer);
+            stream.write_message(b"only").unwrap();
+            stream.flush().unwrap();
+        }
+
+        let cursor = Cursor::new(buffer);
+        let mut stream = SeekableStreamReader::new(cursor);
+        stream.build_index().unwrap();
+        assert!(matches!(
+            stream.seek_to_message(5).unwrap_err(),
+            Error::Custom(_)
+        ));
+    }
+
+    #[test]
+    fn test_seekable_stream_with_index_skips_rebuild() {
+        let mut buffer = Vec::new();
+        {
+            let mut stream = StreamWriter::new(&mut buffer);
+            stream.write_message(b"alpha").unwrap();
+            stream.write_message(b"beta").unwrap();
+            stream.flush().unwrap();
+        }
+
+        let mut indexer = SeekableStreamReader::new(Cursor::new(buffer.clone()));
+        indexer.build_index().unwrap();
+        let index = indexer.into_index();
+
+        let mut stream = SeekableStreamReader::with_index(Cursor::new(buffer), index);
+        assert_eq!(stream.message_count(), 2);
+        assert_eq!(stream.seek_to_message(1).unwrap(), b"beta");
+    }
+
+    #[test]
+    fn test_stream_skip_field() {
+        use crate::writer::Writer;
+
+        let mut writer = Writer::new();
+        writer.write_string_field(1, "skip me").unwrap();
+        writer.write_int32_field(2, 9).unwrap();
+        let data = writer.into_bytes();
+
+        let cursor = Cursor::new(data);
+        let mut stream = StreamReader::new(cursor);
+
+        let tag1 = stream.read_tag().unwrap();
+        stream.skip_field(tag1).unwrap();
+
+        let tag2 = stream.read_tag().unwrap();
+        assert_eq!(tag2.field_number, 2);
+        assert_eq!(stream.read_svarint().unwrap(), 9);
+    }
+
+    #[test]
+    fn test_stream_read_compact_round_trip() {
+        use crate::writer::Writer;
+
+        for &value in &[0u64, 63, 64, 0x3fff, 0x4000, 0x3fff_ffff, 0x4000_0000, u64::MAX] {
+            let mut writer = Writer::new();
+            writer.write_compact_field(1, value).unwrap();
+            let data = writer.into_bytes();
+
+            let mut stream = StreamReader::new(Cursor::new(data));
+            let tag = stream.read_tag().unwrap();
+            assert_eq!(tag.wire_type, WireType::Compact);
+            assert_eq!(stream.read_compact().unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn test_stream_skip_field_compact() {
+        use crate::writer::Writer;
+
+        let mut writer = Writer::new();
+        writer.write_compact_field(1, 0x1234_5678_9abc).unwrap();
+        writer.write_int32_field(2, 9).unwrap();
+        let data = writer.into_bytes();
+
+        let mut stream = StreamReader::new(Cursor::new(data));
+        let tag1 = stream.read_tag().unwrap();
+        stream.skip_field(tag1).unwrap();
+
+        let tag2 = stream.read_tag().unwrap();
+        assert_eq!(tag2.field_number, 2);
+        assert_eq!(stream.read_svarint().unwrap(), 9);
+    }
+
+    #[test]
+    fn test_coded_stream_field_level_round_trip() {
+        let mut buffer = Vec::new();
+        {
+            let mut out = CodedOutputStream::new(&mut buffer);
+            out.write_tag(1, WireType::SVarint).unwrap();
+            out.write_svarint(-42).unwrap();
+            out.write_tag(2, WireType::Bytes).unwrap();
+            out.write_varint(5).unwrap();
+            out.write_bytes(b"hello").unwrap();
+            out.write_tag(3, WireType::Fixed32).unwrap();
+            out.write_fixed32(7).unwrap();
+            out.write_tag(4, WireType::Fixed64).unwrap();
+            out.write_fixed64(99).unwrap();
+            out.flush().unwrap();
+        }
+
+        let mut input = CodedInputStream::new(Cursor::new(buffer));
+
+        let tag = input.read_tag().unwrap();
+        assert_eq!(tag.field_number, 1);
+        assert_eq!(input.read_svarint().unwrap(), -42);
+
+        let tag = input.read_tag().unwrap();
+        assert_eq!(tag.field_number, 2);
+        let length = input.read_varint().unwrap() as usize;
+        assert_eq!(input.read_bytes(length).unwrap(), b"hello");
+
+        let tag = input.read_tag().unwrap();
+        assert_eq!(tag.field_number, 3);
+        assert_eq!(input.read_fixed32().unwrap(), 7);
+
+        let tag = input.read_tag().unwrap();
+        assert_eq!(tag.field_number, 4);
+        assert_eq!(input.read_fixed64().unwrap(), 99);
+    }
+
+    #[test]
+    fn test_coded_input_stream_skip_field() {
+        let mut buffer = Vec::new();
+        {
+            let mut out = CodedOutputStream::new(&mut buffer);
+            out.write_tag(1, WireType::SVarint).unwrap();
+            out.write_svarint(-7).unwrap();
+            out.write_tag(2, WireType::SVarint).unwrap();
+            out.write_svarint(9).unwrap();
+            out.flush().unwrap();
+        }
+
+        let mut input = CodedInputStream::new(Cursor::new(buffer));
+        let tag1 = input.read_tag().unwrap();
+        input.skip_field(tag1).unwrap();
+
+        let tag2 = input.read_tag().unwrap();
+        assert_eq!(tag2.field_number, 2);
+        assert_eq!(input.read_svarint().unwrap(), 9);
+    }
+
+    #[test]
+    fn test_coded_output_stream_flushes_when_buffer_fills() {
+        let mut buffer = Vec::new();
+        {
+            let mut out = CodedOutputStream::with_capacity(4, &mut buffer);
+            out.write_bytes(b"hello world").unwrap();
+            out.flush().unwrap();
+        }
+        assert_eq!(buffer, b"hello world");
+    }
 }
@@ -8,7 +8,8 @@
 /// - 2: Bytes (length-prefixed)
 /// - 3: Fixed32 (4 bytes, little-endian)
 /// - 4: SVarint (ZigZag-encoded signed integer)
-/// - 5-7: Reserved for future use
+/// - 5: Compact (SCALE-style variable-width integer, see `compact_encode`)
+/// - 6-7: Reserved for future use
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
 pub enum WireType {
@@ -22,6 +23,8 @@ pub enum WireType {
     Fixed32 = 3,
     /// ZigZag-encoded signed integer.
     SVarint = 4,
+    /// SCALE-style compact variable-width integer, see `compact_encode`.
+    Compact = 5,
 }
 
 impl WireType {
@@ -33,6 +36,7 @@ impl WireType {
             2 => Some(WireType::Bytes),
             3 => Some(WireType::Fixed32),
             4 => Some(WireType::SVarint),
+            5 => Some(WireType::Compact),
             _ => None,
         }
     }
@@ -48,6 +52,12 @@ pub const TAG_WIRE_TYPE_MASK: u8 = 0x0e;
 pub const TAG_WIRE_TYPE_SHIFT: u8 = 1;
 pub const TAG_FIELD_NUM_SHIFT: u8 = 4;
 pub const MAX_COMPACT_FIELD_NUM: u32 = 15;
+/// Upper bound on the bytes `FieldTag::encode_compact_into` can write: one
+/// marker byte plus up to five varint bytes for a 32-bit field number.
+pub const MAX_COMPACT_TAG_LEN: usize = 6;
+/// Field-number varints in the extended tag format are bounded to 32 bits,
+/// so they take at most ceil(32/7) = 5 bytes.
+const MAX_FIELD_NUM_VARINT_BYTES: usize = 5;
 
 /// Type ID for polymorphic type registration.
 pub type TypeId = u32;
@@ -71,26 +81,52 @@ impl FieldTag {
     /// Encodes the field tag to V2 compact format bytes.
     /// Returns a Vec<u8> containing 1-6 bytes depending on field number.
     pub fn encode_compact(&self) -> Vec<u8> {
+        let mut buf = [0u8; MAX_COMPACT_TAG_LEN];
+        let len = self.encode_compact_into(&mut buf);
+        buf[..len].to_vec()
+    }
+
+    /// Encodes the field tag directly into `buf`, returning the number of
+    /// bytes written. Same output as `encode_compact` but without the
+    /// per-call `Vec` allocation, so hot encode loops can reuse one
+    /// stack-sized buffer (`MAX_COMPACT_TAG_LEN` bytes is always enough)
+    /// across every tag they write.
+    pub fn encode_compact_into(&self, buf: &mut [u8]) -> usize {
         if self.field_number == 0 {
-            return vec![];
+            return 0;
         }
 
         if self.field_number <= MAX_COMPACT_FIELD_NUM {
             // Single byte: [field_num:4][wire_type:3][ext:0]
-            let tag = ((self.field_number as u8) << TAG_FIELD_NUM_SHIFT)
+            buf[0] = ((self.field_number as u8) << TAG_FIELD_NUM_SHIFT)
                 | ((self.wire_type as u8) << TAG_WIRE_TYPE_SHIFT);
-            vec![tag]
+            1
         } else {
             // Extended: [0:4][wire_type:3][ext:1] + varint(field_num)
-            let marker = ((self.wire_type as u8) << TAG_WIRE_TYPE_SHIFT) | TAG_EXTENDED_BIT;
-            let mut result = vec![marker];
+            buf[0] = ((self.wire_type as u8) << TAG_WIRE_TYPE_SHIFT) | TAG_EXTENDED_BIT;
             let mut num = self.field_number;
+            let mut i = 1;
             while num >= 0x80 {
-                result.push((num as u8 & 0x7f) | 0x80);
+                buf[i] = (num as u8 & 0x7f) | 0x80;
                 num >>= 7;
+                i += 1;
             }
-            result.push(num as u8);
-            result
+            buf[i] = num as u8;
+            i + 1
+        }
+    }
+
+    /// Returns the number of bytes `encode_compact` would produce for this
+    /// tag, without actually encoding it. Lets a caller presize an output
+    /// buffer, or compute a nested message's length prefix, in one pass
+    /// instead of building a scratch `Vec` just to measure it.
+    pub fn encoded_compact_len(&self) -> usize {
+        if self.field_number == 0 {
+            0
+        } else if self.field_number <= MAX_COMPACT_FIELD_NUM {
+            1
+        } else {
+            1 + varint_len(self.field_number as u64)
         }
     }
 
@@ -150,32 +186,75 @@ pub fn decode_compact_tag(data: &[u8]) -> Option<CompactTagResult> {
         })
     } else {
         // Extended format: read varint field number
-        let mut field_number: u32 = 0;
-        let mut shift = 0;
-        let mut i = 1;
-        loop {
-            if i >= data.len() {
-                return None; // Buffer underflow
-            }
-            let b = data[i];
-            field_number |= ((b & 0x7f) as u32) << shift;
-            i += 1;
-            if (b & 0x80) == 0 {
-                break;
-            }
-            shift += 7;
-            if shift >= 35 {
-                return None; // Varint overflow
-            }
-        }
+        let (field_number, consumed) = decode_field_number(&data[1..])?;
         Some(CompactTagResult {
             field_number,
             wire_type,
-            bytes_read: i,
+            bytes_read: 1 + consumed,
         })
     }
 }
 
+/// Decodes the field-number varint that follows an extended tag's marker
+/// byte, mirroring the slice/slow split `Reader::read_varint` uses: when
+/// the remaining input is long enough that a full varint chain is
+/// guaranteed to be in bounds, decode it with `decode_field_number_unrolled`
+/// over a bounded window with no per-byte bounds check; otherwise fall back
+/// to the byte-at-a-time loop.
+fn decode_field_number(rest: &[u8]) -> Option<(u32, usize)> {
+    let avail = rest.len();
+    let window = avail.min(MAX_FIELD_NUM_VARINT_BYTES);
+    if window > 0 && (avail >= MAX_FIELD_NUM_VARINT_BYTES || rest[window - 1] < 0x80) {
+        return decode_field_number_unrolled(&rest[..window]);
+    }
+    decode_field_number_slow(rest)
+}
+
+/// Decodes a field-number varint from `data` without per-byte bounds
+/// checks. The caller must ensure `data` either holds
+/// `MAX_FIELD_NUM_VARINT_BYTES` bytes or ends on a byte with its
+/// continuation bit clear.
+fn decode_field_number_unrolled(data: &[u8]) -> Option<(u32, usize)> {
+    let mut field_number: u32 = 0;
+    let mut shift = 0;
+
+    for (i, &b) in data.iter().enumerate().take(MAX_FIELD_NUM_VARINT_BYTES) {
+        field_number |= ((b & 0x7f) as u32) << shift;
+        if (b & 0x80) == 0 {
+            return Some((field_number, i + 1));
+        }
+        shift += 7;
+        if shift >= 35 {
+            return None; // Varint overflow
+        }
+    }
+    None
+}
+
+/// Byte-at-a-time fallback for `decode_field_number`, used only when fewer
+/// than `MAX_FIELD_NUM_VARINT_BYTES` bytes remain after the marker byte.
+fn decode_field_number_slow(rest: &[u8]) -> Option<(u32, usize)> {
+    let mut field_number: u32 = 0;
+    let mut shift = 0;
+    let mut i = 0;
+    loop {
+        if i >= rest.len() {
+            return None; // Buffer underflow
+        }
+        let b = rest[i];
+        field_number |= ((b & 0x7f) as u32) << shift;
+        i += 1;
+        if (b & 0x80) == 0 {
+            break;
+        }
+        shift += 7;
+        if shift >= 35 {
+            return None; // Varint overflow
+        }
+    }
+    Some((field_number, i))
+}
+
 /// Encodes a signed integer using ZigZag encoding.
 #[inline]
 pub fn zigzag_encode_32(n: i32) -> u32 {
@@ -200,6 +279,153 @@ pub fn zigzag_decode_64(n: u64) -> i64 {
     ((n >> 1) as i64) ^ (-((n & 1) as i64))
 }
 
+/// Returns the number of bytes an unsigned varint (LEB128) encoding of
+/// `value` would occupy, without encoding it.
+///
+/// Branches on the value's magnitude rather than looping a byte at a time:
+/// successively tests whether `value` fits in 7, 14, 21, ... bits and
+/// returns the byte count of the first width that covers it, matching the approach
+/// protobuf's `compute_raw_varint64_size` uses.
+#[inline]
+pub fn varint_len(value: u64) -> usize {
+    if value >> 7 == 0 {
+        1
+    } else if value >> 14 == 0 {
+        2
+    } else if value >> 21 == 0 {
+        3
+    } else if value >> 28 == 0 {
+        4
+    } else if value >> 35 == 0 {
+        5
+    } else if value >> 42 == 0 {
+        6
+    } else if value >> 49 == 0 {
+        7
+    } else if value >> 56 == 0 {
+        8
+    } else if value >> 63 == 0 {
+        9
+    } else {
+        10
+    }
+}
+
+/// Returns the number of bytes a ZigZag-encoded signed varint encoding of
+/// `value` would occupy, without encoding it.
+#[inline]
+pub fn svarint_len(value: i64) -> usize {
+    varint_len(zigzag_encode_64(value))
+}
+
+/// Returns the total byte length of a packed-varint field's data (the part
+/// after the `Bytes` length prefix): the sum of each element's `varint_len`.
+/// Lets `Writer::write_packed_uint64` write the length prefix in one pass
+/// instead of encoding into a scratch buffer just to measure it, mirroring
+/// protobuf's `vec_packed_varint_data_size`.
+pub fn packed_varint_data_size(values: impl IntoIterator<Item = u64>) -> usize {
+    values.into_iter().map(varint_len).sum()
+}
+
+/// Returns the total byte length of a packed-svarint field's data, ZigZag
+/// encoding each element before measuring it.
+pub fn packed_svarint_data_size(values: impl IntoIterator<Item = i64>) -> usize {
+    values.into_iter().map(svarint_len).sum()
+}
+
+/// Returns the total byte length of a packed-fixed32 field's data: every
+/// element is exactly 4 bytes.
+pub fn packed_fixed32_data_size(count: usize) -> usize {
+    count * 4
+}
+
+/// Returns the total byte length of a packed-fixed64 field's data: every
+/// element is exactly 8 bytes.
+pub fn packed_fixed64_data_size(count: usize) -> usize {
+    count * 8
+}
+
+/// Returns the total encoded width in bytes for a `Compact` value whose
+/// first byte is `first`, or `None` if the big-integer mode's declared
+/// length would exceed the 8 bytes a `u64` can hold.
+fn compact_width(first: u8) -> Option<usize> {
+    match first & 0b11 {
+        0b00 => Some(1),
+        0b01 => Some(2),
+        0b10 => Some(4),
+        0b11 => {
+            let following = ((first >> 2) as usize) + 4;
+            if following > 8 {
+                None
+            } else {
+                Some(1 + following)
+            }
+        }
+        _ => unreachable!("2-bit mask can only be 0..=3"),
+    }
+}
+
+/// Encodes `value` as a SCALE-style compact variable-width integer,
+/// appending the result to `out`.
+///
+/// The low two bits of the first byte select a mode: `0b00` stores a
+/// value 0-63 in the upper six bits of that single byte; `0b01` is two
+/// bytes holding values up to 2^14-1, value shifted left by 2 and stored
+/// little-endian; `0b10` is four bytes for values up to 2^30-1, same
+/// shift-by-2 LE scheme; `0b11` is "big-integer" mode, where the upper six
+/// bits of the first byte encode `number_of_following_bytes - 4` and that
+/// many little-endian bytes hold the raw value. Always produces the
+/// smallest mode (and, within big-integer mode, the fewest trailing bytes)
+/// that can hold `value`, so two encoders never disagree on the bytes for
+/// the same value.
+pub fn compact_encode(value: u64, out: &mut Vec<u8>) {
+    if value < (1 << 6) {
+        out.push((value as u8) << 2);
+    } else if value < (1 << 14) {
+        out.extend_from_slice(&(((value as u16) << 2) | 0b01).to_le_bytes());
+    } else if value < (1 << 30) {
+        out.extend_from_slice(&(((value as u32) << 2) | 0b10).to_le_bytes());
+    } else {
+        let mut bytes = value.to_le_bytes().to_vec();
+        while bytes.len() > 4 && *bytes.last().unwrap() == 0 {
+            bytes.pop();
+        }
+        let following = bytes.len();
+        out.push((((following - 4) as u8) << 2) | 0b11);
+        out.extend_from_slice(&bytes);
+    }
+}
+
+/// Decodes a SCALE-style compact variable-width integer from the start of
+/// `data`, returning the value and the number of bytes consumed. Returns
+/// `None` if `data` doesn't hold a full encoding, or the big-integer
+/// mode's declared length exceeds 8 bytes.
+///
+/// Accepts non-canonical input (e.g. a value that would fit a smaller
+/// mode, encoded with a larger one) even though `compact_encode` never
+/// produces it — only the mode's bit width determines how many bytes are
+/// read.
+pub fn compact_decode(data: &[u8]) -> Option<(u64, usize)> {
+    let first = *data.first()?;
+    let width = compact_width(first)?;
+    if data.len() < width {
+        return None;
+    }
+
+    let value = match first & 0b11 {
+        0b00 => (first >> 2) as u64,
+        0b01 => (u16::from_le_bytes([data[0], data[1]]) >> 2) as u64,
+        0b10 => (u32::from_le_bytes([data[0], data[1], data[2], data[3]]) >> 2) as u64,
+        0b11 => {
+            let mut buf = [0u8; 8];
+            buf[..width - 1].copy_from_slice(&data[1..width]);
+            u64::from_le_bytes(buf)
+        }
+        _ => unreachable!("2-bit mask can only be 0..=3"),
+    };
+    Some((value, width))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -336,4 +562,211 @@ mod tests {
         assert_eq!(decoded.field_number, 0);
         assert_eq!(decoded.bytes_read, 1);
     }
+
+    #[test]
+    fn test_varint_len_matches_actual_encoded_length() {
+        // Boundary values just below and at each 7-bit threshold.
+        let cases: &[(u64, usize)] = &[
+            (0, 1),
+            (1, 1),
+            (0x7f, 1),
+            (0x80, 2),
+            (0x3fff, 2),
+            (0x4000, 3),
+            (0x1fffff, 3),
+            (0x200000, 4),
+            (u32::MAX as u64, 5),
+            (u64::MAX, 10),
+        ];
+        for &(value, expected_len) in cases {
+            assert_eq!(varint_len(value), expected_len, "value = {value:#x}");
+
+            let mut buf = vec![];
+            let mut v = value;
+            loop {
+                let byte = (v as u8 & 0x7f) | if v >= 0x80 { 0x80 } else { 0 };
+                buf.push(byte);
+                v >>= 7;
+                if v == 0 {
+                    break;
+                }
+            }
+            assert_eq!(varint_len(value), buf.len(), "value = {value:#x}");
+        }
+    }
+
+    #[test]
+    fn test_svarint_len_round_trips_through_zigzag() {
+        assert_eq!(svarint_len(0), varint_len(zigzag_encode_64(0)));
+        assert_eq!(svarint_len(-1), 1);
+        assert_eq!(svarint_len(64), varint_len(zigzag_encode_64(64)));
+        assert_eq!(svarint_len(i64::MIN), varint_len(zigzag_encode_64(i64::MIN)));
+    }
+
+    #[test]
+    fn test_encode_compact_into_matches_encode_compact() {
+        for field_number in [0, 1, 15, 16, 127, 128, 1000, 1 << 20] {
+            for wire_type in [
+                WireType::Varint,
+                WireType::Fixed64,
+                WireType::Bytes,
+                WireType::Fixed32,
+                WireType::SVarint,
+            ] {
+                let tag = FieldTag::new(field_number, wire_type);
+                let expected = tag.encode_compact();
+
+                let mut buf = [0u8; MAX_COMPACT_TAG_LEN];
+                let len = tag.encode_compact_into(&mut buf);
+                assert_eq!(len, expected.len(), "field_number = {field_number}");
+                assert_eq!(&buf[..len], expected.as_slice(), "field_number = {field_number}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_compact_tag_fast_path_with_trailing_bytes() {
+        // Extended tag for field 1000, followed by unrelated trailing data;
+        // the fast path must stop at the varint's terminator, not consume
+        // the whole buffer.
+        let tag = FieldTag::new(1000, WireType::SVarint);
+        let mut encoded = tag.encode_compact();
+        encoded.extend_from_slice(&[0xff, 0xff, 0xff]);
+
+        let decoded = decode_compact_tag(&encoded).unwrap();
+        assert_eq!(decoded.field_number, 1000);
+        assert_eq!(decoded.wire_type, WireType::SVarint);
+        assert_eq!(decoded.bytes_read, tag.encode_compact().len());
+    }
+
+    #[test]
+    fn test_decode_compact_tag_near_buffer_end_falls_back_to_slow_path() {
+        // Extended tag followed by a single byte with its continuation bit
+        // set, so fewer than MAX_FIELD_NUM_VARINT_BYTES are available and the
+        // window does not end on a terminator: exercises
+        // decode_field_number_slow rather than the unrolled path.
+        let tag = FieldTag::new(200, WireType::Bytes);
+        let mut encoded = tag.encode_compact();
+        let tag_len = encoded.len();
+        assert!(tag_len < 1 + MAX_FIELD_NUM_VARINT_BYTES);
+        encoded.push(0x80);
+
+        let decoded = decode_compact_tag(&encoded).unwrap();
+        assert_eq!(decoded.field_number, 200);
+        assert_eq!(decoded.wire_type, WireType::Bytes);
+        assert_eq!(decoded.bytes_read, tag_len);
+    }
+
+    #[test]
+    fn test_decode_compact_tag_truncated_field_number_is_none() {
+        // Marker byte claims an extended tag but no varint bytes follow.
+        let marker = (WireType::Varint as u8) << TAG_WIRE_TYPE_SHIFT | TAG_EXTENDED_BIT;
+        assert!(decode_compact_tag(&[marker]).is_none());
+
+        // Continuation bit set on the final byte available.
+        assert!(decode_compact_tag(&[marker, 0x80]).is_none());
+    }
+
+    #[test]
+    fn test_compact_encode_decode_round_trip_each_mode() {
+        let cases: &[(u64, usize)] = &[
+            (0, 1),
+            (63, 1),
+            (64, 2),
+            (0x3fff, 2),
+            (0x4000, 4),
+            (0x3fffffff, 4),
+            (0x40000000, 5), // smallest big-integer value needs 4 following bytes
+            (u32::MAX as u64, 5),
+            (u64::MAX, 9),
+        ];
+        for &(value, expected_len) in cases {
+            let mut out = Vec::new();
+            compact_encode(value, &mut out);
+            assert_eq!(out.len(), expected_len, "value = {value:#x}");
+
+            let (decoded, consumed) = compact_decode(&out).unwrap();
+            assert_eq!(decoded, value, "value = {value:#x}");
+            assert_eq!(consumed, expected_len, "value = {value:#x}");
+        }
+    }
+
+    #[test]
+    fn test_compact_decode_with_trailing_bytes_only_consumes_its_own_width() {
+        let mut out = vec![];
+        compact_encode(300, &mut out);
+        out.extend_from_slice(&[0xaa, 0xbb, 0xcc]);
+
+        let (decoded, consumed) = compact_decode(&out).unwrap();
+        assert_eq!(decoded, 300);
+        assert_eq!(consumed, 2);
+    }
+
+    #[test]
+    fn test_compact_decode_accepts_non_canonical_encoding() {
+        // Big-integer mode (mode bits 0b11) encoding the small value 5 in
+        // 4 following bytes, which `compact_encode` would never produce.
+        let data = [0b11, 5, 0, 0, 0];
+        let (decoded, consumed) = compact_decode(&data).unwrap();
+        assert_eq!(decoded, 5);
+        assert_eq!(consumed, 5);
+    }
+
+    #[test]
+    fn test_compact_decode_rejects_oversized_big_integer_length() {
+        // Mode 0b11 with (following_bytes - 4) = 5 declares 9 following
+        // bytes, one more than a u64 can hold.
+        let first_byte = (5u8 << 2) | 0b11;
+        assert!(compact_decode(&[first_byte, 0, 0, 0, 0, 0, 0, 0, 0, 0]).is_none());
+    }
+
+    #[test]
+    fn test_compact_decode_truncated_input_is_none() {
+        assert!(compact_decode(&[]).is_none());
+
+        // Mode 0b01 claims 2 bytes total but only 1 is present.
+        let first_byte = (1u8 << 2) | 0b01;
+        assert!(compact_decode(&[first_byte]).is_none());
+    }
+
+    #[test]
+    fn test_packed_varint_data_size_matches_sum_of_varint_len() {
+        let values: Vec<u64> = vec![0, 1, 127, 128, 300, u64::MAX];
+        let expected: usize = values.iter().map(|&v| varint_len(v)).sum();
+        assert_eq!(packed_varint_data_size(values.iter().copied()), expected);
+    }
+
+    #[test]
+    fn test_packed_svarint_data_size_matches_sum_of_svarint_len() {
+        let values: Vec<i64> = vec![0, -1, 1, -64, 64, i64::MIN];
+        let expected: usize = values.iter().map(|&v| svarint_len(v)).sum();
+        assert_eq!(packed_svarint_data_size(values.iter().copied()), expected);
+    }
+
+    #[test]
+    fn test_packed_fixed_data_sizes() {
+        assert_eq!(packed_fixed32_data_size(3), 12);
+        assert_eq!(packed_fixed64_data_size(3), 24);
+        assert_eq!(packed_fixed32_data_size(0), 0);
+    }
+
+    #[test]
+    fn test_encoded_compact_len_matches_encode_compact() {
+        for field_number in [0, 1, 15, 16, 127, 128, 1000, 1 << 20] {
+            for wire_type in [
+                WireType::Varint,
+                WireType::Fixed64,
+                WireType::Bytes,
+                WireType::Fixed32,
+                WireType::SVarint,
+            ] {
+                let tag = FieldTag::new(field_number, wire_type);
+                assert_eq!(
+                    tag.encoded_compact_len(),
+                    tag.encode_compact().len(),
+                    "field_number = {field_number}, wire_type = {wire_type:?}"
+                );
+            }
+        }
+    }
 }
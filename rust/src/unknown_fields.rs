@@ -0,0 +1,76 @@
+//! Capture and round-trip preservation for fields a decoder doesn't
+//! recognize.
+//!
+//! Without this, `Reader::skip_field` discards unrecognized tags entirely,
+//! so a decode/re-encode cycle silently drops any field a newer writer
+//! added that this reader doesn't know about yet. Enabling capture mode on
+//! a `Reader` keeps those tags (and their exact encoded bytes) around so
+//! they can be re-emitted with `Writer::write_unknown_fields`.
+
+use crate::types::WireType;
+
+/// A single captured unknown field.
+///
+/// `data` is the exact, already wire-encoded span the field occupied after
+/// its tag: for `Bytes` fields this includes the length prefix, so
+/// re-emitting it verbatim requires no re-encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnknownField<'a> {
+    pub wire_type: WireType,
+    pub data: &'a [u8],
+}
+
+/// A collection of unknown fields captured during decoding, in the order
+/// they were encountered.
+#[derive(Debug, Clone, Default)]
+pub struct UnknownFields<'a> {
+    fields: Vec<(u32, UnknownField<'a>)>,
+}
+
+impl<'a> UnknownFields<'a> {
+    /// Creates an empty collection.
+    pub fn new() -> Self {
+        Self { fields: Vec::new() }
+    }
+
+    /// Records a captured field.
+    pub(crate) fn push(&mut self, field_number: u32, wire_type: WireType, data: &'a [u8]) {
+        self.fields.push((field_number, UnknownField { wire_type, data }));
+    }
+
+    /// Returns true if no unknown fields were captured.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Returns the number of captured fields.
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    /// Iterates over the captured fields in encounter order.
+    pub fn iter(&self) -> impl Iterator<Item = (u32, UnknownField<'a>)> + '_ {
+        self.fields.iter().map(|(num, field)| (*num, *field))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_fields_push_and_iter() {
+        let mut unknown = UnknownFields::new();
+        assert!(unknown.is_empty());
+
+        unknown.push(5, WireType::Varint, &[0x2a]);
+        unknown.push(9, WireType::Bytes, &[0x03, b'f', b'o', b'o']);
+
+        assert_eq!(unknown.len(), 2);
+        let collected: Vec<_> = unknown.iter().collect();
+        assert_eq!(collected[0].0, 5);
+        assert_eq!(collected[0].1.wire_type, WireType::Varint);
+        assert_eq!(collected[1].0, 9);
+        assert_eq!(collected[1].1.data, &[0x03, b'f', b'o', b'o']);
+    }
+}
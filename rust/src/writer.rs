@@ -1,13 +1,54 @@
 //! Cramberry encoder.
 
-use crate::error::Result;
-use crate::types::{zigzag_encode_32, zigzag_encode_64, FieldTag, WireType, END_MARKER};
+use crate::compression;
+use crate::error::{Error, Result};
+use crate::types::{
+    compact_encode, packed_fixed32_data_size, packed_fixed64_data_size, packed_svarint_data_size,
+    packed_varint_data_size, zigzag_encode_32, zigzag_encode_64, FieldTag, WireType, END_MARKER,
+    MAX_COMPACT_TAG_LEN,
+};
+use crate::unknown_fields::UnknownFields;
 
 const INITIAL_CAPACITY: usize = 256;
 
+/// Width in bytes of the placeholder `Writer::begin_message_field` reserves
+/// for a nested message's length prefix: wide enough to hold any `u32`
+/// length LEB128-encoded with continuation-bit padding, so `end_message_field`
+/// can always patch the real length in place without moving the body that
+/// follows it.
+const MESSAGE_LENGTH_PLACEHOLDER_WIDTH: usize = 5;
+
+/// Encodes `value` as a non-minimal LEB128 varint padded to exactly
+/// `MESSAGE_LENGTH_PLACEHOLDER_WIDTH` bytes (every byte but the last keeps
+/// its continuation bit set even once `value`'s bits run out). A decoder
+/// following the standard "stop at the first byte without the continuation
+/// bit" rule reads this identically to a minimal encoding.
+fn write_padded_length(buf: &mut [u8], mut value: u32) {
+    debug_assert_eq!(buf.len(), MESSAGE_LENGTH_PLACEHOLDER_WIDTH);
+    for (i, byte) in buf.iter_mut().enumerate() {
+        let has_more = i + 1 < MESSAGE_LENGTH_PLACEHOLDER_WIDTH;
+        *byte = (value as u8 & 0x7f) | if has_more { 0x80 } else { 0 };
+        value >>= 7;
+    }
+}
+
+/// An in-progress nested message field opened by `Writer::begin_message_field`
+/// and closed by `Writer::end_message_field`. Opaque: the only valid use of
+/// one is passing it to `end_message_field` on the same `Writer`.
+pub struct MessageScope {
+    /// Offset of the reserved length placeholder within the writer's buffer.
+    length_offset: usize,
+    /// Number of scopes already open when this one was created, so
+    /// `end_message_field` can detect a scope closed out of LIFO order.
+    depth: usize,
+}
+
 /// Writer encodes Cramberry data into a binary buffer.
 pub struct Writer {
     buffer: Vec<u8>,
+    /// Offsets of currently open `MessageScope` length placeholders, used to
+    /// reject unbalanced `begin_message_field`/`end_message_field` pairs.
+    open_scopes: Vec<usize>,
 }
 
 impl Writer {
@@ -20,6 +61,7 @@ impl Writer {
     pub fn with_capacity(capacity: usize) -> Self {
         Self {
             buffer: Vec::with_capacity(capacity),
+            open_scopes: Vec::new(),
         }
     }
 
@@ -43,6 +85,16 @@ impl Writer {
         self.buffer
     }
 
+    /// Consumes the writer, writing the encoded bytes to `w` in one call.
+    ///
+    /// Lets large or streamed output go straight to a socket or file
+    /// without an intermediate `Vec<u8>` at the call site, mirroring
+    /// `OwnedReader::from_read` on the decode side.
+    pub fn into_writer<W: std::io::Write>(self, w: &mut W) -> Result<()> {
+        w.write_all(&self.buffer)?;
+        Ok(())
+    }
+
     /// Resets the writer for reuse.
     pub fn reset(&mut self) {
         self.buffer.clear();
@@ -51,8 +103,9 @@ impl Writer {
     /// Writes a V2 compact field tag.
     pub fn write_tag(&mut self, field_number: u32, wire_type: WireType) -> Result<()> {
         let tag = FieldTag::new(field_number, wire_type);
-        let encoded = tag.encode_compact();
-        self.buffer.extend_from_slice(&encoded);
+        let mut buf = [0u8; MAX_COMPACT_TAG_LEN];
+        let len = tag.encode_compact_into(&mut buf);
+        self.buffer.extend_from_slice(&buf[..len]);
         Ok(())
     }
 
@@ -153,6 +206,19 @@ impl Writer {
         Ok(())
     }
 
+    /// Writes a SCALE-style compact variable-width integer (`WireType::Compact`).
+    /// Denser than LEB128 for small values; see `compact_encode` for the format.
+    pub fn write_compact(&mut self, value: u64) -> Result<()> {
+        compact_encode(value, &mut self.buffer);
+        Ok(())
+    }
+
+    /// Writes a tagged field with a compact-encoded value.
+    pub fn write_compact_field(&mut self, field_number: u32, value: u64) -> Result<()> {
+        self.write_tag(field_number, WireType::Compact)?;
+        self.write_compact(value)
+    }
+
     /// Writes a length-prefixed string.
     pub fn write_string(&mut self, value: &str) -> Result<()> {
         self.write_varint(value.len() as u32)?;
@@ -220,6 +286,206 @@ impl Writer {
         self.write_tag(field_number, WireType::Bytes)?;
         self.write_length_prefixed_bytes(value)
     }
+
+    /// Writes a tagged `Bytes` field whose payload is DEFLATE-compressed.
+    ///
+    /// Opt-in: plain `write_bytes_field` fields are untouched and stay on
+    /// the zero-copy `&[u8]` read path. Pair with `Reader::read_compressed_bytes`.
+    pub fn write_compressed_bytes_field(&mut self, field_number: u32, value: &[u8]) -> Result<()> {
+        let payload = compression::compress(value)?;
+        self.write_tag(field_number, WireType::Bytes)?;
+        self.write_length_prefixed_bytes(&payload)
+    }
+
+    /// Writes a packed repeated field of zigzag-encoded `int32` values: a
+    /// single `Bytes`-wire-type field whose payload is the back-to-back
+    /// varint encodings, pairing with `Reader::read_packed_int32`.
+    ///
+    /// The `Bytes` length prefix is computed up front from
+    /// `packed_varint_data_size` over the ZigZag-encoded values rather than
+    /// assembled in a scratch `Writer`, since every element's encoded
+    /// length is already known.
+    pub fn write_packed_int32(&mut self, field_number: u32, values: &[i32]) -> Result<()> {
+        let data_size = packed_varint_data_size(values.iter().map(|&v| zigzag_encode_32(v) as u64));
+        self.write_tag(field_number, WireType::Bytes)?;
+        self.write_varint(data_size as u32)?;
+        for &value in values {
+            self.write_svarint(value)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a packed repeated field of `uint64` values, pairing with
+    /// `Reader::read_packed_uint64`.
+    pub fn write_packed_uint64(&mut self, field_number: u32, values: &[u64]) -> Result<()> {
+        let data_size = packed_varint_data_size(values.iter().copied());
+        self.write_tag(field_number, WireType::Bytes)?;
+        self.write_varint(data_size as u32)?;
+        for &value in values {
+            self.write_varint64(value)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a packed repeated field of zigzag-encoded `int64` values,
+    /// pairing with `Reader::read_packed_svarint64`.
+    pub fn write_packed_svarint64(&mut self, field_number: u32, values: &[i64]) -> Result<()> {
+        let data_size = packed_svarint_data_size(values.iter().copied());
+        self.write_tag(field_number, WireType::Bytes)?;
+        self.write_varint(data_size as u32)?;
+        for &value in values {
+            self.write_svarint64(value)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a packed repeated field of fixed 32-bit values, pairing with
+    /// `Reader::read_packed_fixed32`.
+    pub fn write_packed_fixed32(&mut self, field_number: u32, values: &[u32]) -> Result<()> {
+        self.write_tag(field_number, WireType::Bytes)?;
+        self.write_varint(packed_fixed32_data_size(values.len()) as u32)?;
+        for &value in values {
+            self.write_fixed32(value)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a packed repeated field of fixed 64-bit values, pairing with
+    /// `Reader::read_packed_fixed64`.
+    pub fn write_packed_fixed64(&mut self, field_number: u32, values: &[u64]) -> Result<()> {
+        self.write_tag(field_number, WireType::Bytes)?;
+        self.write_varint(packed_fixed64_data_size(values.len()) as u32)?;
+        for &value in values {
+            self.write_fixed64(value)?;
+        }
+        Ok(())
+    }
+
+    /// Starts a nested message field: writes the tag and reserves a
+    /// placeholder for its length prefix, returning a `MessageScope` the
+    /// caller must pass to `end_message_field` once the nested fields are
+    /// written directly onto this same `Writer`.
+    ///
+    /// Unlike `write_bytes_field`, the nested body doesn't need to be
+    /// assembled in a scratch `Writer` first: the length isn't known until
+    /// `end_message_field`, so this reserves a fixed-width placeholder now
+    /// and patches the real length into it in place, with no memmove of the
+    /// body that follows.
+    pub fn begin_message_field(&mut self, field_number: u32) -> Result<MessageScope> {
+        self.write_tag(field_number, WireType::Bytes)?;
+        let length_offset = self.buffer.len();
+        self.buffer
+            .extend_from_slice(&[0u8; MESSAGE_LENGTH_PLACEHOLDER_WIDTH]);
+        let depth = self.open_scopes.len();
+        self.open_scopes.push(length_offset);
+        Ok(MessageScope { length_offset, depth })
+    }
+
+    /// Closes a `MessageScope` opened by `begin_message_field`, patching the
+    /// placeholder with the body length now that it's known.
+    ///
+    /// Returns `Error::UnbalancedMessageScope` if `scope` isn't the most
+    /// recently opened, still-open scope on this `Writer` — e.g. a nested
+    /// `begin_message_field` call whose own `end_message_field` was skipped.
+    pub fn end_message_field(&mut self, scope: MessageScope) -> Result<()> {
+        if self.open_scopes.last() != Some(&scope.length_offset) || scope.depth != self.open_scopes.len() - 1 {
+            return Err(Error::UnbalancedMessageScope);
+        }
+        self.open_scopes.pop();
+
+        let body_start = scope.length_offset + MESSAGE_LENGTH_PLACEHOLDER_WIDTH;
+        let body_length = (self.buffer.len() - body_start) as u32;
+        write_padded_length(
+            &mut self.buffer[scope.length_offset..body_start],
+            body_length,
+        );
+        Ok(())
+    }
+
+    /// Writes a tagged field holding an associative collection.
+    ///
+    /// Each entry is encoded as `key_bytes` (via `encode_key`) and
+    /// `value_bytes` (via `encode_value`), independent of the scalar wire
+    /// types `K`/`V` would otherwise use. Entries are sorted by their
+    /// encoded key bytes before writing, so two runtimes encoding the same
+    /// map (e.g. this crate and the Go implementation, or two calls to
+    /// this function with the same `HashMap`, whose iteration order is
+    /// otherwise unspecified) produce byte-identical output — important
+    /// for golden-file comparison and content hashing.
+    pub fn write_map_field<K, V>(
+        &mut self,
+        field_number: u32,
+        map: &std::collections::HashMap<K, V>,
+        mut encode_key: impl FnMut(&mut Writer, &K) -> Result<()>,
+        mut encode_value: impl FnMut(&mut Writer, &V) -> Result<()>,
+    ) -> Result<()> {
+        let mut entries: Vec<(Vec<u8>, Vec<u8>)> = Vec::with_capacity(map.len());
+        for (key, value) in map {
+            let mut key_writer = Writer::new();
+            encode_key(&mut key_writer, key)?;
+            let mut value_writer = Writer::new();
+            encode_value(&mut value_writer, value)?;
+            entries.push((key_writer.into_bytes(), value_writer.into_bytes()));
+        }
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut body = Writer::new();
+        body.write_varint(entries.len() as u32)?;
+        for (key_bytes, value_bytes) in &entries {
+            body.write_bytes_field(1, key_bytes)?;
+            body.write_bytes_field(2, value_bytes)?;
+        }
+        self.write_bytes_field(field_number, body.as_bytes())
+    }
+
+    /// Writes a tagged field holding a set.
+    ///
+    /// Each element is encoded via `encode_elem` and, like
+    /// `write_map_field`, elements are sorted by their encoded bytes before
+    /// writing for cross-runtime determinism.
+    pub fn write_set_field<T>(
+        &mut self,
+        field_number: u32,
+        set: &std::collections::HashSet<T>,
+        mut encode_elem: impl FnMut(&mut Writer, &T) -> Result<()>,
+    ) -> Result<()> {
+        let mut elements: Vec<Vec<u8>> = Vec::with_capacity(set.len());
+        for item in set {
+            let mut elem_writer = Writer::new();
+            encode_elem(&mut elem_writer, item)?;
+            elements.push(elem_writer.into_bytes());
+        }
+        elements.sort();
+
+        let mut body = Writer::new();
+        body.write_varint(elements.len() as u32)?;
+        for elem_bytes in &elements {
+            body.write_bytes_field(1, elem_bytes)?;
+        }
+        self.write_bytes_field(field_number, body.as_bytes())
+    }
+
+    /// Re-emits previously captured unknown fields verbatim.
+    ///
+    /// Each field's raw bytes are already a complete wire encoding (for
+    /// `Bytes` fields, including the length prefix), so this just writes
+    /// the tag followed by the stored bytes with no re-encoding.
+    pub fn write_unknown_fields(&mut self, unknown: &UnknownFields<'_>) -> Result<()> {
+        for (field_number, field) in unknown.iter() {
+            self.write_tag(field_number, field.wire_type)?;
+            self.write_bytes(field.data)?;
+        }
+        Ok(())
+    }
+
+    /// Starts a canonical encoding of a message: fields written through the
+    /// returned `CanonicalWriter` are buffered and flushed in ascending
+    /// field-number order on `CanonicalWriter::finish`, so that a message
+    /// built with the same field values always produces the same bytes
+    /// regardless of the order the caller wrote them in.
+    pub fn canonical() -> CanonicalWriter {
+        CanonicalWriter::new()
+    }
 }
 
 impl Default for Writer {
@@ -228,6 +494,117 @@ impl Default for Writer {
     }
 }
 
+/// Buffers `write_*_field` calls for a single message and flushes them in
+/// ascending field-number order, rejecting duplicate field numbers.
+///
+/// `Writer` already emits varints in their shortest form, so the only
+/// normalization left for a canonical encoding is ordering: `Writer`
+/// commits each field's bytes to the output buffer as soon as it's
+/// written, which bakes in call order. `CanonicalWriter` defers that by
+/// encoding each field into its own scratch `Writer` first and only
+/// appending to the real output once the whole message is known, at
+/// `finish`. Nested messages built the same way (recursively canonical)
+/// keep the guarantee at every depth.
+pub struct CanonicalWriter {
+    fields: Vec<(u32, Vec<u8>)>,
+    seen: std::collections::HashSet<u32>,
+}
+
+impl CanonicalWriter {
+    /// Creates an empty canonical message buffer.
+    pub fn new() -> Self {
+        Self {
+            fields: Vec::new(),
+            seen: std::collections::HashSet::new(),
+        }
+    }
+
+    /// Encodes one field into a scratch `Writer` via `encode`, recording it
+    /// for later ordering. Returns `Error::custom` if `field_number` was
+    /// already written for this message.
+    fn record(
+        &mut self,
+        field_number: u32,
+        encode: impl FnOnce(&mut Writer) -> Result<()>,
+    ) -> Result<()> {
+        if !self.seen.insert(field_number) {
+            return Err(Error::custom(format!(
+                "duplicate field number {} in canonical message",
+                field_number
+            )));
+        }
+        let mut scratch = Writer::new();
+        encode(&mut scratch)?;
+        self.fields.push((field_number, scratch.into_bytes()));
+        Ok(())
+    }
+
+    /// Writes a tagged field with boolean value.
+    pub fn write_bool_field(&mut self, field_number: u32, value: bool) -> Result<()> {
+        self.record(field_number, |w| w.write_bool_field(field_number, value))
+    }
+
+    /// Writes a tagged field with int32 value.
+    pub fn write_int32_field(&mut self, field_number: u32, value: i32) -> Result<()> {
+        self.record(field_number, |w| w.write_int32_field(field_number, value))
+    }
+
+    /// Writes a tagged field with int64 value.
+    pub fn write_int64_field(&mut self, field_number: u32, value: i64) -> Result<()> {
+        self.record(field_number, |w| w.write_int64_field(field_number, value))
+    }
+
+    /// Writes a tagged field with uint32 value.
+    pub fn write_uint32_field(&mut self, field_number: u32, value: u32) -> Result<()> {
+        self.record(field_number, |w| w.write_uint32_field(field_number, value))
+    }
+
+    /// Writes a tagged field with uint64 value.
+    pub fn write_uint64_field(&mut self, field_number: u32, value: u64) -> Result<()> {
+        self.record(field_number, |w| w.write_uint64_field(field_number, value))
+    }
+
+    /// Writes a tagged field with float32 value.
+    pub fn write_float32_field(&mut self, field_number: u32, value: f32) -> Result<()> {
+        self.record(field_number, |w| w.write_float32_field(field_number, value))
+    }
+
+    /// Writes a tagged field with float64 value.
+    pub fn write_float64_field(&mut self, field_number: u32, value: f64) -> Result<()> {
+        self.record(field_number, |w| w.write_float64_field(field_number, value))
+    }
+
+    /// Writes a tagged field with string value.
+    pub fn write_string_field(&mut self, field_number: u32, value: &str) -> Result<()> {
+        self.record(field_number, |w| w.write_string_field(field_number, value))
+    }
+
+    /// Writes a tagged field with bytes value.
+    pub fn write_bytes_field(&mut self, field_number: u32, value: &[u8]) -> Result<()> {
+        self.record(field_number, |w| w.write_bytes_field(field_number, value))
+    }
+
+    /// Sorts the buffered fields ascending by field number, flushes them
+    /// followed by the end marker, and returns the underlying `Writer`.
+    pub fn finish(self) -> Result<Writer> {
+        let mut fields = self.fields;
+        fields.sort_by_key(|(field_number, _)| *field_number);
+
+        let mut out = Writer::new();
+        for (_, bytes) in fields {
+            out.write_bytes(&bytes)?;
+        }
+        out.write_end_marker()?;
+        Ok(out)
+    }
+}
+
+impl Default for CanonicalWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -276,4 +653,105 @@ mod tests {
         writer.write_string("hello").unwrap();
         assert_eq!(writer.as_bytes(), &[5, b'h', b'e', b'l', b'l', b'o']);
     }
+
+    #[test]
+    fn test_canonical_writer_sorts_fields_regardless_of_call_order() {
+        let mut forward = Writer::canonical();
+        forward.write_int32_field(1, 1).unwrap();
+        forward.write_int32_field(2, 2).unwrap();
+        forward.write_int32_field(3, 3).unwrap();
+        let forward = forward.finish().unwrap().into_bytes();
+
+        let mut backward = Writer::canonical();
+        backward.write_int32_field(3, 3).unwrap();
+        backward.write_int32_field(2, 2).unwrap();
+        backward.write_int32_field(1, 1).unwrap();
+        let backward = backward.finish().unwrap().into_bytes();
+
+        assert_eq!(forward, backward);
+    }
+
+    #[test]
+    fn test_canonical_writer_rejects_duplicate_field_number() {
+        let mut writer = Writer::canonical();
+        writer.write_int32_field(1, 1).unwrap();
+        assert!(writer.write_string_field(1, "dup").is_err());
+    }
+
+    #[test]
+    fn test_into_writer_matches_into_bytes() {
+        let mut writer = Writer::new();
+        writer.write_int32_field(1, 42).unwrap();
+        let expected = writer.as_bytes().to_vec();
+
+        let mut writer = Writer::new();
+        writer.write_int32_field(1, 42).unwrap();
+        let mut out = Vec::new();
+        writer.into_writer(&mut out).unwrap();
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn test_message_field_round_trip() {
+        use crate::reader::Reader;
+
+        let mut writer = Writer::new();
+        let scope = writer.begin_message_field(1).unwrap();
+        writer.write_int32_field(1, 42).unwrap();
+        writer.write_string_field(2, "nested").unwrap();
+        writer.end_message_field(scope).unwrap();
+        let data = writer.into_bytes();
+
+        let mut reader = Reader::new(&data);
+        let tag = reader.read_tag().unwrap();
+        assert_eq!(tag.field_number, 1);
+        let length = reader.read_varint().unwrap() as usize;
+        let mut nested = reader.sub_reader(length).unwrap();
+
+        let tag = nested.read_tag().unwrap();
+        assert_eq!(tag.field_number, 1);
+        assert_eq!(nested.read_int32().unwrap(), 42);
+        let tag = nested.read_tag().unwrap();
+        assert_eq!(tag.field_number, 2);
+        assert_eq!(nested.read_string().unwrap(), "nested");
+        assert!(!nested.has_more());
+    }
+
+    #[test]
+    fn test_nested_message_fields_round_trip() {
+        use crate::reader::Reader;
+
+        let mut writer = Writer::new();
+        let outer = writer.begin_message_field(1).unwrap();
+        writer.write_int32_field(1, 1).unwrap();
+        let inner = writer.begin_message_field(2).unwrap();
+        writer.write_int32_field(1, 2).unwrap();
+        writer.end_message_field(inner).unwrap();
+        writer.end_message_field(outer).unwrap();
+        let data = writer.into_bytes();
+
+        let mut reader = Reader::new(&data);
+        reader.read_tag().unwrap();
+        let outer_length = reader.read_varint().unwrap() as usize;
+        let mut outer_reader = reader.sub_reader(outer_length).unwrap();
+
+        outer_reader.read_tag().unwrap();
+        assert_eq!(outer_reader.read_int32().unwrap(), 1);
+
+        let tag = outer_reader.read_tag().unwrap();
+        assert_eq!(tag.field_number, 2);
+        let inner_length = outer_reader.read_varint().unwrap() as usize;
+        let mut inner_reader = outer_reader.sub_reader(inner_length).unwrap();
+        inner_reader.read_tag().unwrap();
+        assert_eq!(inner_reader.read_int32().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_end_message_field_rejects_out_of_order_scope() {
+        let mut writer = Writer::new();
+        let outer = writer.begin_message_field(1).unwrap();
+        let inner = writer.begin_message_field(2).unwrap();
+        assert!(writer.end_message_field(outer).is_err());
+        writer.end_message_field(inner).unwrap();
+    }
 }